@@ -0,0 +1,821 @@
+use std::{
+    cmp::Ordering,
+    fmt::Display,
+    ops::{Add, BitAnd, BitOr, BitXor, Mul, Neg, Not, Shl, Shr, Sub},
+    str::FromStr,
+};
+
+/// An arbitrary-precision signed integer. The value is stored as a sign and a
+/// little-endian vector of 32-bit limbs. The magnitude never carries trailing
+/// zero limbs and zero is never negative, so the derived equality and hashing
+/// are structural.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    negative: bool,
+    magnitude: Vec<u32>,
+}
+
+/// The number of decimal digits that always fit in a single limb, used to
+/// parse decimal strings several digits at a time.
+const DECIMAL_CHUNK_DIGITS: usize = 9;
+
+impl BigInt {
+    fn from_parts(negative: bool, mut magnitude: Vec<u32>) -> BigInt {
+        trim(&mut magnitude);
+        let negative = negative && !magnitude.is_empty();
+        BigInt {
+            negative,
+            magnitude,
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn is_odd(&self) -> bool {
+        self.magnitude.first().is_some_and(|low| low & 1 == 1)
+    }
+
+    pub fn abs(&self) -> BigInt {
+        BigInt::from_parts(false, self.magnitude.clone())
+    }
+
+    /// Returns the number of bits needed to represent the magnitude of this integer.
+    pub fn bits(&self) -> usize {
+        match self.magnitude.last() {
+            Some(top) => self.magnitude.len() * 32 - top.leading_zeros() as usize,
+            None => 0,
+        }
+    }
+
+    /// Converts a float to an integer by truncating it toward zero, or returns
+    /// `None` if the float is infinite or not a number.
+    pub fn from_f64(value: f64) -> Option<BigInt> {
+        if !value.is_finite() {
+            return None;
+        }
+        let bits = value.to_bits();
+        let exponent = ((bits >> 52) & 0x7ff) as i64;
+        if exponent == 0 {
+            return Some(BigInt::default()); // Subnormal floats are always less than one
+        }
+        let mantissa = BigInt::from(((bits & ((1 << 52) - 1)) | (1 << 52)) as i64);
+        let shift = exponent - 1075;
+        let magnitude = if shift >= 0 {
+            &mantissa << shift as usize
+        } else {
+            &mantissa >> (-shift) as usize
+        };
+        Some(if value < 0.0 { -magnitude } else { magnitude })
+    }
+
+    /// Returns the largest integer whose square is less than or equal to this
+    /// integer, using Newton's method. Panics if this integer is negative.
+    pub fn isqrt(&self) -> BigInt {
+        assert!(
+            !self.negative,
+            "attempted to take the square root of a negative BigInt"
+        );
+        if self.is_zero() {
+            return BigInt::default();
+        }
+        // Start from a power of two that is guaranteed to be above the root,
+        // so that each step moves monotonically down toward it.
+        let mut estimate = &BigInt::from(1) << (self.bits() / 2 + 1);
+        loop {
+            let next = &(&estimate + &self.div_rem(&estimate).0) >> 1;
+            if next >= estimate {
+                return estimate;
+            }
+            estimate = next;
+        }
+    }
+
+    /// Converts this integer to a `u64`, or returns `None` if it is negative or too large.
+    pub fn to_u64(&self) -> Option<u64> {
+        match (self.negative, self.magnitude.as_slice()) {
+            (true, _) => None,
+            (false, []) => Some(0),
+            (false, [low]) => Some(*low as u64),
+            (false, [low, high]) => Some(((*high as u64) << 32) | *low as u64),
+            _ => None,
+        }
+    }
+
+    /// Parses a non-empty string of digits in the given radix, which must be
+    /// between 2 and 36, or returns `None` if any of the digits are invalid.
+    pub fn from_str_radix(digits: &str, radix: u32) -> Option<BigInt> {
+        if digits.is_empty() {
+            return None;
+        }
+        let mut magnitude = vec![];
+        for char in digits.chars() {
+            mul_add_small(&mut magnitude, radix, char.to_digit(radix)?);
+        }
+        Some(BigInt::from_parts(false, magnitude))
+    }
+
+    /// Formats this integer as a string of lowercase digits in the given
+    /// radix, which must be between 2 and 36, preceded by a sign if negative.
+    pub fn to_str_radix(&self, radix: u32) -> String {
+        let digits = self.magnitude_to_str_radix(radix);
+        if self.negative {
+            format!("-{}", digits)
+        } else {
+            digits
+        }
+    }
+
+    /// Formats the magnitude of this integer in the given radix. Digits are
+    /// produced in chunks, using the largest power of the radix that fits in a
+    /// single limb, to keep the number of long divisions down.
+    fn magnitude_to_str_radix(&self, radix: u32) -> String {
+        if self.is_zero() {
+            return String::from("0");
+        }
+        let mut chunk = radix;
+        let mut chunk_digits = 1;
+        while let Some(next) = chunk.checked_mul(radix) {
+            chunk = next;
+            chunk_digits += 1;
+        }
+        let mut chunks = vec![];
+        let mut magnitude = self.magnitude.clone();
+        while !magnitude.is_empty() {
+            let (quotient, remainder) = div_rem_small(&magnitude, chunk);
+            chunks.push(remainder);
+            magnitude = quotient;
+        }
+        let mut digits = String::new();
+        for (i, chunk) in chunks.iter().rev().enumerate() {
+            let mut chunk_string = vec![];
+            let mut value = *chunk;
+            while value > 0 {
+                chunk_string.push(std::char::from_digit(value % radix, radix).unwrap()); // Guaranteed to succeed because the digit is less than the radix
+                value /= radix;
+            }
+            // Every chunk but the leading one is padded to its full width.
+            if i > 0 {
+                chunk_string.resize(chunk_digits, '0');
+            }
+            digits.extend(chunk_string.iter().rev());
+        }
+        digits
+    }
+
+    /// Converts this integer to the nearest double-precision float, or to an
+    /// infinity if it is out of range.
+    pub fn to_f64(&self) -> f64 {
+        let magnitude = self
+            .magnitude
+            .iter()
+            .rev()
+            .fold(0.0, |acc, limb| acc * 4294967296.0 + *limb as f64);
+        if self.negative {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+
+    /// Returns this integer in two's complement form, sign-extended to the
+    /// given number of limbs. The length must leave room for the sign bit.
+    fn to_twos_complement(&self, len: usize) -> Vec<u32> {
+        let mut limbs = self.magnitude.clone();
+        limbs.resize(len, 0);
+        if self.negative {
+            let mut carry = true;
+            for limb in limbs.iter_mut() {
+                (*limb, carry) = (!*limb).overflowing_add(carry as u32);
+            }
+        }
+        limbs
+    }
+
+    fn from_twos_complement(mut limbs: Vec<u32>) -> BigInt {
+        let negative = limbs.last().is_some_and(|top| top >> 31 == 1);
+        if negative {
+            let mut carry = true;
+            for limb in limbs.iter_mut() {
+                (*limb, carry) = (!*limb).overflowing_add(carry as u32);
+            }
+        }
+        BigInt::from_parts(negative, limbs)
+    }
+
+    /// Applies a bitwise operation to each pair of limbs, treating both
+    /// integers as if they were stored in two's complement with infinite sign
+    /// extension.
+    fn bitwise(&self, other: &BigInt, operation: impl Fn(u32, u32) -> u32) -> BigInt {
+        let len = self.magnitude.len().max(other.magnitude.len()) + 1;
+        let limbs = self
+            .to_twos_complement(len)
+            .into_iter()
+            .zip(other.to_twos_complement(len))
+            .map(|(a, b)| operation(a, b))
+            .collect();
+        BigInt::from_twos_complement(limbs)
+    }
+
+    /// Raises this integer to the given power using repeated squaring.
+    pub fn pow(&self, mut exponent: u32) -> BigInt {
+        let mut base = self.clone();
+        let mut result = BigInt::from(1);
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = &result * &base;
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = &base * &base;
+            }
+        }
+        result
+    }
+
+    /// Raises this integer to a non-negative power and reduces the result
+    /// modulo a positive modulus, without ever computing the full power.
+    pub fn pow_mod(&self, exponent: &BigInt, modulus: &BigInt) -> BigInt {
+        let mut base = self.rem_euclid(modulus);
+        let mut result = BigInt::from(1).rem_euclid(modulus);
+        for i in 0..exponent.bits() {
+            if exponent.magnitude[i / 32] >> (i % 32) & 1 == 1 {
+                result = (&result * &base).rem_euclid(modulus);
+            }
+            base = (&base * &base).rem_euclid(modulus);
+        }
+        result
+    }
+
+    /// Returns the greatest common divisor of the magnitudes of two integers,
+    /// which is zero only when both integers are zero.
+    pub fn gcd(&self, other: &BigInt) -> BigInt {
+        let mut a = self.abs();
+        let mut b = other.abs();
+        while !b.is_zero() {
+            let remainder = a.div_rem(&b).1;
+            a = b;
+            b = remainder;
+        }
+        a
+    }
+
+    /// Returns the inverse of this integer modulo a positive modulus, which
+    /// is the residue that gives 1 when multiplied by it, or `None` if the
+    /// two are not coprime, in which case there is no inverse.
+    pub fn mod_inverse(&self, modulus: &BigInt) -> Option<BigInt> {
+        // The extended Euclidean algorithm, which tracks the coefficient of
+        // this integer in each remainder
+        let (mut a, mut b) = (self.rem_euclid(modulus), modulus.clone());
+        let (mut x, mut y) = (BigInt::from(1), BigInt::from(0));
+        while !b.is_zero() {
+            let (quotient, remainder) = a.div_rem(&b);
+            let next = &x - &(&quotient * &y);
+            (a, b) = (b, remainder);
+            (x, y) = (y, next);
+        }
+        (a == BigInt::from(1)).then(|| x.rem_euclid(modulus))
+    }
+
+    /// Divides this integer by another and returns the quotient and the
+    /// remainder. The quotient is truncated toward zero and the remainder
+    /// takes the sign of the dividend, matching Rust's primitive integers.
+    /// Panics if the divisor is zero.
+    pub fn div_rem(&self, other: &BigInt) -> (BigInt, BigInt) {
+        assert!(!other.is_zero(), "attempted to divide a BigInt by zero");
+        let (quotient, remainder) = div_rem_magnitude(&self.magnitude, &other.magnitude);
+        (
+            BigInt::from_parts(self.negative != other.negative, quotient),
+            BigInt::from_parts(self.negative, remainder),
+        )
+    }
+
+    /// Divides this integer by another and rounds the quotient toward negative
+    /// infinity. Panics if the divisor is zero.
+    pub fn div_floor(&self, other: &BigInt) -> BigInt {
+        let (quotient, remainder) = self.div_rem(other);
+        if !remainder.is_zero() && remainder.negative != other.negative {
+            &quotient - &BigInt::from(1)
+        } else {
+            quotient
+        }
+    }
+
+    /// Returns the least non-negative remainder of dividing this integer by
+    /// another. Panics if the divisor is zero.
+    pub fn rem_euclid(&self, other: &BigInt) -> BigInt {
+        let (_, remainder) = self.div_rem(other);
+        if remainder.negative {
+            &remainder + &other.abs()
+        } else {
+            remainder
+        }
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(value: i64) -> Self {
+        let magnitude = value.unsigned_abs();
+        BigInt::from_parts(value < 0, vec![magnitude as u32, (magnitude >> 32) as u32])
+    }
+}
+
+impl From<i32> for BigInt {
+    fn from(value: i32) -> Self {
+        BigInt::from(value as i64)
+    }
+}
+
+impl FromStr for BigInt {
+    type Err = ();
+
+    /// Parses an optionally signed string of decimal digits.
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(());
+        }
+        let mut magnitude = vec![];
+        let leading = digits.len() % DECIMAL_CHUNK_DIGITS;
+        let mut start = 0;
+        let mut end = if leading == 0 {
+            DECIMAL_CHUNK_DIGITS
+        } else {
+            leading
+        };
+        while start < digits.len() {
+            let chunk: u32 = digits[start..end].parse().unwrap(); // Guaranteed to succeed because all bytes are digits
+            let scale = 10u32.pow((end - start) as u32);
+            mul_add_small(&mut magnitude, scale, chunk);
+            start = end;
+            end += DECIMAL_CHUNK_DIGITS;
+        }
+        Ok(BigInt::from_parts(negative, magnitude))
+    }
+}
+
+impl Display for BigInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad_integral(!self.negative, "", &self.magnitude_to_str_radix(10))
+    }
+}
+
+/// Debug output is the decimal value, since tokens holding integers are
+/// debug-formatted in the messages of validation errors.
+impl std::fmt::Debug for BigInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_magnitude(&self.magnitude, &other.magnitude),
+            (true, true) => cmp_magnitude(&other.magnitude, &self.magnitude),
+        }
+    }
+}
+
+impl Neg for BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::from_parts(!self.negative, self.magnitude)
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::from_parts(
+                self.negative,
+                add_magnitude(&self.magnitude, &other.magnitude),
+            );
+        }
+        match cmp_magnitude(&self.magnitude, &other.magnitude) {
+            Ordering::Equal => BigInt::default(),
+            Ordering::Greater => BigInt::from_parts(
+                self.negative,
+                sub_magnitude(&self.magnitude, &other.magnitude),
+            ),
+            Ordering::Less => BigInt::from_parts(
+                other.negative,
+                sub_magnitude(&other.magnitude, &self.magnitude),
+            ),
+        }
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &-other.clone()
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        BigInt::from_parts(
+            self.negative != other.negative,
+            mul_magnitude(&self.magnitude, &other.magnitude),
+        )
+    }
+}
+
+impl Shl<usize> for &BigInt {
+    type Output = BigInt;
+
+    /// Multiplies this integer by two raised to the given power.
+    fn shl(self, bits: usize) -> BigInt {
+        let mut magnitude = vec![0u32; bits / 32];
+        magnitude.extend(shl_bits(&self.magnitude, (bits % 32) as u32));
+        BigInt::from_parts(self.negative, magnitude)
+    }
+}
+
+impl Shr<usize> for &BigInt {
+    type Output = BigInt;
+
+    /// Divides this integer by two raised to the given power, rounding toward
+    /// negative infinity like an arithmetic shift in two's complement.
+    fn shr(self, bits: usize) -> BigInt {
+        let limbs = bits / 32;
+        if limbs >= self.magnitude.len() {
+            return BigInt::from(if self.negative { -1 } else { 0 });
+        }
+        let shift = (bits % 32) as u32;
+        let truncated =
+            BigInt::from_parts(self.negative, shr_bits(&self.magnitude[limbs..], shift));
+        // Truncating a negative integer rounds it toward zero, so it needs
+        // one more step down whenever any of the discarded bits were set.
+        let discarded = self.magnitude[..limbs].iter().any(|limb| *limb != 0)
+            || self.magnitude[limbs] & ((1u64 << shift) - 1) as u32 != 0;
+        if self.negative && discarded {
+            &truncated - &BigInt::from(1)
+        } else {
+            truncated
+        }
+    }
+}
+
+impl BitAnd for &BigInt {
+    type Output = BigInt;
+
+    fn bitand(self, other: &BigInt) -> BigInt {
+        self.bitwise(other, |a, b| a & b)
+    }
+}
+
+impl BitOr for &BigInt {
+    type Output = BigInt;
+
+    fn bitor(self, other: &BigInt) -> BigInt {
+        self.bitwise(other, |a, b| a | b)
+    }
+}
+
+impl BitXor for &BigInt {
+    type Output = BigInt;
+
+    fn bitxor(self, other: &BigInt) -> BigInt {
+        self.bitwise(other, |a, b| a ^ b)
+    }
+}
+
+impl Not for BigInt {
+    type Output = BigInt;
+
+    /// Inverts every bit in two's complement, which is equivalent to `-x - 1`.
+    fn not(self) -> BigInt {
+        -self - BigInt::from(1)
+    }
+}
+
+impl Add for BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: BigInt) -> BigInt {
+        &self + &other
+    }
+}
+
+impl Sub for BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: BigInt) -> BigInt {
+        &self - &other
+    }
+}
+
+impl Mul for BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: BigInt) -> BigInt {
+        &self * &other
+    }
+}
+
+/// Removes trailing zero limbs so that every value has a unique representation.
+fn trim(magnitude: &mut Vec<u32>) {
+    while let Some(0) = magnitude.last() {
+        magnitude.pop();
+    }
+}
+
+fn cmp_magnitude(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut output = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0u64;
+    for (i, limb) in long.iter().enumerate() {
+        let sum = *limb as u64 + *short.get(i).unwrap_or(&0) as u64 + carry;
+        output.push(sum as u32);
+        carry = sum >> 32;
+    }
+    output.push(carry as u32);
+    output
+}
+
+/// Subtracts magnitude `b` from magnitude `a`. Assumes that `a >= b`.
+fn sub_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut output = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, limb) in a.iter().enumerate() {
+        let difference = *limb as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        output.push(difference as u32);
+        borrow = if difference < 0 { 1 } else { 0 };
+    }
+    output
+}
+
+fn mul_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut output = vec![0u32; a.len() + b.len()];
+    for (i, x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, y) in b.iter().enumerate() {
+            let product = *x as u64 * *y as u64 + output[i + j] as u64 + carry;
+            output[i + j] = product as u32;
+            carry = product >> 32;
+        }
+        output[i + b.len()] = carry as u32;
+    }
+    output
+}
+
+/// Multiplies a magnitude in place by a single limb and adds another limb.
+fn mul_add_small(magnitude: &mut Vec<u32>, factor: u32, addend: u32) {
+    let mut carry = addend as u64;
+    for limb in magnitude.iter_mut() {
+        let product = *limb as u64 * factor as u64 + carry;
+        *limb = product as u32;
+        carry = product >> 32;
+    }
+    if carry > 0 {
+        magnitude.push(carry as u32);
+    }
+}
+
+fn div_rem_small(a: &[u32], divisor: u32) -> (Vec<u32>, u32) {
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder = 0u64;
+    for (i, limb) in a.iter().enumerate().rev() {
+        let current = (remainder << 32) | *limb as u64;
+        quotient[i] = (current / divisor as u64) as u32;
+        remainder = current % divisor as u64;
+    }
+    trim(&mut quotient);
+    (quotient, remainder as u32)
+}
+
+/// Shifts a magnitude left by fewer than 32 bits. The output always has one
+/// more limb than the input to hold any bits shifted out of the top.
+fn shl_bits(a: &[u32], shift: u32) -> Vec<u32> {
+    let mut output = Vec::with_capacity(a.len() + 1);
+    let mut carry = 0u32;
+    for limb in a {
+        output.push((limb << shift) | carry);
+        carry = if shift == 0 { 0 } else { limb >> (32 - shift) };
+    }
+    output.push(carry);
+    output
+}
+
+/// Shifts a magnitude right by fewer than 32 bits.
+fn shr_bits(a: &[u32], shift: u32) -> Vec<u32> {
+    let mut output = vec![0u32; a.len()];
+    for i in 0..a.len() {
+        let high = if shift == 0 {
+            0
+        } else {
+            a.get(i + 1).map_or(0, |limb| limb << (32 - shift))
+        };
+        output[i] = (a[i] >> shift) | high;
+    }
+    trim(&mut output);
+    output
+}
+
+/// Divides two magnitudes using the schoolbook long division algorithm
+/// described by Knuth (The Art of Computer Programming, Vol. 2, 4.3.1, Algorithm D).
+/// Assumes that the divisor is non-zero.
+fn div_rem_magnitude(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if cmp_magnitude(a, b) == Ordering::Less {
+        return (vec![], a.to_vec());
+    }
+    if b.len() == 1 {
+        let (quotient, remainder) = div_rem_small(a, b[0]);
+        return (quotient, vec![remainder]);
+    }
+
+    // Normalize so that the top limb of the divisor has its high bit set,
+    // which keeps each estimated quotient digit within two of the real one.
+    let shift = b.last().unwrap().leading_zeros(); // Guaranteed to exist because the divisor is non-zero
+    let mut divisor = shl_bits(b, shift);
+    divisor.pop();
+    let mut dividend = shl_bits(a, shift);
+    let n = divisor.len();
+    let m = dividend.len() - n;
+    let mut quotient = vec![0u32; m];
+
+    for j in (0..m).rev() {
+        let top = ((dividend[j + n] as u64) << 32) | dividend[j + n - 1] as u64;
+        let mut estimate = top / divisor[n - 1] as u64;
+        let mut remainder = top % divisor[n - 1] as u64;
+        while estimate > u32::MAX as u64
+            || estimate * divisor[n - 2] as u64 > ((remainder << 32) | dividend[j + n - 2] as u64)
+        {
+            estimate -= 1;
+            remainder += divisor[n - 1] as u64;
+            if remainder > u32::MAX as u64 {
+                break;
+            }
+        }
+
+        // Multiply and subtract the scaled divisor from the current window.
+        let mut borrow = 0i64;
+        let mut carry = 0u64;
+        for i in 0..n {
+            let product = estimate * divisor[i] as u64 + carry;
+            carry = product >> 32;
+            let difference = dividend[i + j] as i64 - borrow - (product & 0xffff_ffff) as i64;
+            dividend[i + j] = difference as u32;
+            borrow = if difference < 0 { 1 } else { 0 };
+        }
+        let difference = dividend[j + n] as i64 - borrow - carry as i64;
+        dividend[j + n] = difference as u32;
+
+        // The estimate was one too large, so add the divisor back.
+        if difference < 0 {
+            estimate -= 1;
+            let mut carry = 0u64;
+            for i in 0..n {
+                let sum = dividend[i + j] as u64 + divisor[i] as u64 + carry;
+                dividend[i + j] = sum as u32;
+                carry = sum >> 32;
+            }
+            dividend[j + n] = dividend[j + n].wrapping_add(carry as u32);
+        }
+        quotient[j] = estimate as u32;
+    }
+
+    trim(&mut quotient);
+    (quotient, shr_bits(&dividend[..n], shift))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BigInt;
+
+    fn big(s: &str) -> BigInt {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parsing_and_display_round_trip() {
+        for s in [
+            "0",
+            "7",
+            "-42",
+            "4294967296",
+            "-123456789012345678901234567890",
+        ] {
+            assert_eq!(big(s).to_string(), s);
+            assert_eq!(format!("{:?}", big(s)), s);
+        }
+        assert_eq!(big("-0").to_string(), "0");
+        assert_eq!(big("000123").to_string(), "123");
+    }
+
+    #[test]
+    fn parsing_and_formatting_handle_other_radixes() {
+        let value = BigInt::from_str_radix("ffff", 16).unwrap();
+        assert_eq!(value.to_string(), "65535");
+        assert_eq!(value.to_str_radix(2), "1111111111111111");
+        assert_eq!((-big("35")).to_str_radix(36), "-z");
+        assert_eq!(big("0").to_str_radix(8), "0");
+        assert_eq!(
+            big("340282366920938463463374607431768211456").to_str_radix(16),
+            "100000000000000000000000000000000"
+        );
+        assert!(BigInt::from_str_radix("102", 2).is_none());
+    }
+
+    #[test]
+    fn arithmetic_behaves_correctly_across_limbs() {
+        let a = big("99999999999");
+        assert_eq!((&a * &a).to_string(), "9999999999800000000001");
+        assert_eq!((&a + &a).to_string(), "199999999998");
+        assert_eq!((&big("5") - &a).to_string(), "-99999999994");
+        assert_eq!((&a - &a).to_string(), "0");
+    }
+
+    #[test]
+    fn bitwise_operations_use_twos_complement() {
+        assert_eq!((&big("12") & &big("10")).to_string(), "8");
+        assert_eq!((&big("12") | &big("-10")).to_string(), "-2");
+        assert_eq!((&big("-12") ^ &big("10")).to_string(), "-2");
+        assert_eq!(
+            (&big("-4294967296") & &big("-1")).to_string(),
+            "-4294967296"
+        );
+        assert_eq!((!big("0")).to_string(), "-1");
+        assert_eq!((&big("-5") >> 1).to_string(), "-3");
+        assert_eq!((&big("-4294967296") >> 32).to_string(), "-1");
+        assert_eq!((&big("-4294967297") >> 32).to_string(), "-2");
+        assert_eq!((&big("-7") >> 100).to_string(), "-1");
+        assert_eq!((&big("1") << 70).to_string(), "1180591620717411303424");
+    }
+
+    #[test]
+    fn square_roots_and_float_conversions_behave_correctly() {
+        assert_eq!(big("0").isqrt().to_string(), "0");
+        assert_eq!(big("15").isqrt().to_string(), "3");
+        assert_eq!(big("16").isqrt().to_string(), "4");
+        assert_eq!(
+            big("152415787532388367504942236884722755800955129")
+                .isqrt()
+                .to_string(),
+            "12345678901234567890123"
+        );
+        assert_eq!(BigInt::from_f64(-2.9).unwrap().to_string(), "-2");
+        assert_eq!(
+            BigInt::from_f64(1e20).unwrap().to_string(),
+            "100000000000000000000"
+        );
+        assert_eq!(BigInt::from_f64(0.5).unwrap().to_string(), "0");
+        assert!(BigInt::from_f64(f64::NAN).is_none());
+    }
+
+    #[test]
+    fn modular_inverses_exist_only_for_coprime_integers() {
+        assert_eq!(big("3").mod_inverse(&big("11")).unwrap().to_string(), "4");
+        assert_eq!(big("-3").mod_inverse(&big("11")).unwrap().to_string(), "7");
+        let modulus = big("99999999999999999999");
+        let inverse = big("65537").mod_inverse(&modulus).unwrap();
+        assert_eq!(
+            (&inverse * &big("65537")).rem_euclid(&modulus).to_string(),
+            "1"
+        );
+        assert!(big("4").mod_inverse(&big("8")).is_none());
+        assert!(big("0").mod_inverse(&big("7")).is_none());
+    }
+
+    #[test]
+    fn division_truncates_toward_zero() {
+        let (q, r) = big("-7").div_rem(&big("2"));
+        assert_eq!((q.to_string(), r.to_string()), ("-3".into(), "-1".into()));
+
+        let a = big("123456789012345678901234567890123456789");
+        let b = big("987654321098765432109");
+        let (q, r) = a.div_rem(&b);
+        assert_eq!(q.to_string(), "124999998860937500");
+        assert_eq!((&(&q * &b) + &r), a);
+    }
+}
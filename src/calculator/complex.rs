@@ -0,0 +1,268 @@
+use std::fmt::Display;
+
+use super::{
+    functions,
+    number::Number,
+    options::{ComplexFormat, Options},
+    types::{Error, Operator, Result},
+    value::Value,
+    Calculator,
+};
+
+/// The largest exponent for which a power of an exact complex number is
+/// computed exactly. Larger powers are approximated with floats, since the
+/// parts of the exact result would grow too large to be useful.
+const MAX_EXACT_EXPONENT: u64 = 1024;
+
+/// A complex number made up of a real part and an imaginary part, such as
+/// `1+2i`. Each part is a number of its own, so the parts of complex numbers
+/// with exact parts stay exact under addition, subtraction, multiplication,
+/// and division.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Complex {
+    pub re: Number,
+    pub im: Number,
+}
+
+impl Complex {
+    pub fn new(re: Number, im: Number) -> Self {
+        Complex { re, im }
+    }
+
+    /// Returns whether both parts are zero.
+    pub fn is_zero(&self) -> bool {
+        self.re.is_zero() && self.im.is_zero()
+    }
+
+    /// Returns the distance of this number from zero, approximated with a float.
+    pub fn modulus(&self) -> f64 {
+        self.re.to_f64().hypot(self.im.to_f64())
+    }
+
+    /// Returns the angle of this number from the positive real axis, in
+    /// radians from -π to π.
+    pub fn argument(&self) -> f64 {
+        self.im.to_f64().atan2(self.re.to_f64())
+    }
+
+    /// Formats this number for display using the formatting settings from the
+    /// options, either with its parts, such as `1+2i`, or in polar form, such
+    /// as `2.23606797749979∠1.1071487177940904`, with the angle in the unit set
+    /// by the angle mode. The output radix only applies to plain integers, so
+    /// the parts are always decimal.
+    pub fn format(&self, options: &Options) -> String {
+        if options.complex_format == ComplexFormat::Polar {
+            let angle = options.angle.angle_from_radians(self.argument());
+            return format!("{}∠{}", Number::Float(self.modulus()), Number::Float(angle));
+        }
+        let options = &Options {
+            radix: 10,
+            ..options.clone()
+        };
+        Complex::join(self.re.format(options), self.im.format(options))
+    }
+
+    /// Joins the formatted parts of a complex number, leaving out a real part
+    /// of zero and the digit of an imaginary part of one, so that `0+1i` is
+    /// written as `i`.
+    fn join(re: String, im: String) -> String {
+        let im = match im.as_str() {
+            "1" => String::new(),
+            "-1" => String::from("-"),
+            _ => im,
+        };
+        if re == "0" {
+            format!("{}i", im)
+        } else if im.starts_with('-') {
+            format!("{}{}i", re, im)
+        } else {
+            format!("{}+{}i", re, im)
+        }
+    }
+}
+
+impl From<Number> for Complex {
+    fn from(value: Number) -> Self {
+        Complex::new(value, Number::from(0))
+    }
+}
+
+impl Display for Complex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            Complex::join(self.re.to_string(), self.im.to_string())
+        )
+    }
+}
+
+/// Returns whether an operator can be applied to complex numbers.
+pub fn supports(operator: Operator) -> bool {
+    matches!(
+        operator,
+        Operator::Add
+            | Operator::Subtract
+            | Operator::Multiply
+            | Operator::ImplicitMultiply
+            | Operator::Of
+            | Operator::Divide
+            | Operator::Power
+    )
+}
+
+/// Returns whether a power of real numbers is complex, which is the case for
+/// a negative base and an exponent that is not a whole number, as in
+/// `(-4)^0.5`.
+pub fn is_complex_power(base: &Number, exponent: &Number) -> bool {
+    base.to_f64() < 0.0 && exponent.to_f64().fract() != 0.0
+}
+
+/// Applies an arithmetic operator to two complex numbers.
+pub fn apply_binary_operator(
+    operator: Operator,
+    a: Complex,
+    b: Complex,
+    options: &Options,
+) -> Result<Value> {
+    match operator {
+        Operator::Add => add(a, b, options).map(Value::from),
+        Operator::Subtract => subtract(a, b, options).map(Value::from),
+        Operator::Multiply | Operator::ImplicitMultiply | Operator::Of => {
+            multiply(a, b, options).map(Value::from)
+        }
+        Operator::Divide => divide(a, b, options).map(Value::from),
+        Operator::Power => power(a, b, options),
+        _ => unreachable!(), // Only called for operators that support complex numbers
+    }
+}
+
+/// Creates a complex number from its distance from zero and its angle, which
+/// is in the unit set by the angle mode, as in `2∠90` in degrees being `2i`.
+pub fn from_polar(modulus: Number, angle: Number, options: &Options) -> Result<Value> {
+    let re = functions::cos(vec![angle.clone()], options)?;
+    let im = functions::sin(vec![angle], options)?;
+    Ok(Value::from(Complex::new(
+        Calculator::multiply(modulus.clone(), re, options)?,
+        Calculator::multiply(modulus, im, options)?,
+    )))
+}
+
+pub fn negate(a: Complex, options: &Options) -> Result<Complex> {
+    Ok(Complex::new(
+        Calculator::negate(a.re, options)?,
+        Calculator::negate(a.im, options)?,
+    ))
+}
+
+fn add(a: Complex, b: Complex, options: &Options) -> Result<Complex> {
+    Ok(Complex::new(
+        Calculator::add(a.re, b.re, options)?,
+        Calculator::add(a.im, b.im, options)?,
+    ))
+}
+
+fn subtract(a: Complex, b: Complex, options: &Options) -> Result<Complex> {
+    Ok(Complex::new(
+        Calculator::subtract(a.re, b.re, options)?,
+        Calculator::subtract(a.im, b.im, options)?,
+    ))
+}
+
+/// Multiplies two complex numbers: (a + bi)(c + di) = (ac - bd) + (ad + bc)i.
+fn multiply(a: Complex, b: Complex, options: &Options) -> Result<Complex> {
+    let ac = Calculator::multiply(a.re.clone(), b.re.clone(), options)?;
+    let bd = Calculator::multiply(a.im.clone(), b.im.clone(), options)?;
+    let ad = Calculator::multiply(a.re, b.im, options)?;
+    let bc = Calculator::multiply(a.im, b.re, options)?;
+    Ok(Complex::new(
+        Calculator::subtract(ac, bd, options)?,
+        Calculator::add(ad, bc, options)?,
+    ))
+}
+
+/// Divides two complex numbers by multiplying both by the conjugate of the
+/// divisor: (a + bi) / (c + di) = ((ac + bd) + (bc - ad)i) / (c^2 + d^2).
+fn divide(a: Complex, b: Complex, options: &Options) -> Result<Complex> {
+    if b.is_zero() {
+        return Err(Error::DivideByZero(
+            Operator::Divide,
+            Box::new(Value::from(a)),
+            Box::new(Value::from(b)),
+        ));
+    }
+    let conjugate = Complex::new(b.re.clone(), Calculator::negate(b.im.clone(), options)?);
+    let numerator = multiply(a, conjugate, options)?;
+    let denominator = Calculator::add(
+        Calculator::multiply(b.re.clone(), b.re, options)?,
+        Calculator::multiply(b.im.clone(), b.im, options)?,
+        options,
+    )?;
+    Ok(Complex::new(
+        Calculator::divide(numerator.re, denominator.clone(), options)?,
+        Calculator::divide(numerator.im, denominator, options)?,
+    ))
+}
+
+/// Raises a complex number to a power. Exact numbers raised to small integer
+/// exponents are computed exactly by repeated multiplication. Everything else
+/// is approximated as `exp(b * ln(a))`, which yields the principal value.
+fn power(a: Complex, b: Complex, options: &Options) -> Result<Value> {
+    let exponent = match (&b.re, b.im.is_zero()) {
+        (Number::Integer(exponent), true) if a.re.is_exact() && a.im.is_exact() => exponent.clone(),
+        // A negative real base raised to a real power that is a half, such
+        // as in `(-4)^0.5`, is purely imaginary, which the approximation
+        // below would miss by a rounding error in the real part
+        (exponent, true)
+            if a.im.is_zero() && a.re.to_f64() < 0.0 && exponent.to_f64().fract().abs() == 0.5 =>
+        {
+            let c = exponent.to_f64();
+            let magnitude = a.re.to_f64().abs().powf(c);
+            let sign = if (c - 0.5).rem_euclid(2.0) == 0.0 {
+                1.0
+            } else {
+                -1.0
+            };
+            return Ok(Value::from(Complex::new(
+                Number::from(0),
+                Number::Float(sign * magnitude),
+            )));
+        }
+        _ => {
+            let (modulus, argument) = (a.modulus(), a.argument());
+            let (c, d) = (b.re.to_f64(), b.im.to_f64());
+            let ln_modulus = modulus.ln();
+            let magnitude = (c * ln_modulus - d * argument).exp();
+            let angle = d * ln_modulus + c * argument;
+            return Ok(Value::from(Complex::new(
+                Number::Float(magnitude * angle.cos()),
+                Number::Float(magnitude * angle.sin()),
+            )));
+        }
+    };
+    match exponent.abs().to_u64() {
+        Some(count) if count <= MAX_EXACT_EXPONENT => {
+            let mut result = Complex::from(Number::from(1));
+            let mut base = a;
+            let mut count = count;
+            while count > 0 {
+                if count % 2 == 1 {
+                    result = multiply(result, base.clone(), options)?;
+                }
+                count /= 2;
+                if count > 0 {
+                    base = multiply(base.clone(), base, options)?;
+                }
+            }
+            if exponent.is_negative() {
+                result = divide(Complex::from(Number::from(1)), result, options)?;
+            }
+            Ok(Value::from(result))
+        }
+        _ => power(
+            Complex::new(Number::Float(a.re.to_f64()), Number::Float(a.im.to_f64())),
+            b,
+            options,
+        ),
+    }
+}
@@ -0,0 +1,103 @@
+use std::f64::consts::{E, PI, TAU};
+
+use super::{
+    bigint::BigInt,
+    complex::Complex,
+    date::DateTime,
+    number::Number,
+    options::{NumericMode, Options},
+    rational::Rational,
+    value::Value,
+};
+
+/// The number of digits computed beyond those that are displayed, so that
+/// rounding errors in the computation do not reach the displayed digits.
+const GUARD_DIGITS: usize = 10;
+
+/// Returns the value of a named constant, or `None` if there is no constant
+/// with that name. The imaginary unit `i` and the dates `now` and `today`,
+/// which are read from the clock, are the only constants that are not real.
+pub fn lookup(name: &str, options: &Options) -> Option<Value> {
+    match name {
+        "i" => Some(Value::Complex(Complex::new(
+            Number::from(0),
+            Number::from(1),
+        ))),
+        "now" => Some(Value::Date(DateTime::now(options))),
+        "today" => Some(Value::Date(DateTime::today(options))),
+        _ => real(name, options).map(Value::Number),
+    }
+}
+
+/// Returns the value of a named real constant. In float mode, constants are the closest floats to their
+/// true values. In rational mode, they are computed as exact fractions that
+/// are accurate to the number of digits set in the options, so that the
+/// digits displayed in decimal format are all correct.
+fn real(name: &str, options: &Options) -> Option<Number> {
+    if name == "inf" {
+        return Some(Number::Float(f64::INFINITY)); // Has no rational representation
+    }
+    if options.mode == NumericMode::Float {
+        let value = match name {
+            "pi" => PI,
+            "tau" => TAU,
+            "e" => E,
+            "phi" => (1.0 + 5f64.sqrt()) / 2.0,
+            _ => return None,
+        };
+        return Some(Number::Float(value));
+    }
+    let scale = BigInt::from(10).pow((options.digits + GUARD_DIGITS) as u32);
+    let value = match name {
+        "pi" => pi(&scale),
+        "tau" => &pi(&scale) * &BigInt::from(2),
+        "e" => e(&scale),
+        "phi" => {
+            (&scale + &(&(&scale * &scale) * &BigInt::from(5)).isqrt())
+                .div_rem(&2.into())
+                .0
+        }
+        _ => return None,
+    };
+    Some(Number::from(Rational::new(value, scale)))
+}
+
+/// Computes pi, multiplied by `scale`, using Machin's formula:
+/// pi = 16 * atan(1/5) - 4 * atan(1/239).
+fn pi(scale: &BigInt) -> BigInt {
+    &(&arctan_inverse(5, scale) * &BigInt::from(16))
+        - &(&arctan_inverse(239, scale) * &BigInt::from(4))
+}
+
+/// Computes atan(1/x), multiplied by `scale`, using its Taylor series:
+/// atan(1/x) = 1/x - 1/(3x^3) + 1/(5x^5) - ...
+fn arctan_inverse(x: i64, scale: &BigInt) -> BigInt {
+    let x_squared = BigInt::from(x * x);
+    let mut power = scale.div_rem(&x.into()).0;
+    let mut sum = power.clone();
+    let mut k: i64 = 1;
+    while !power.is_zero() {
+        power = power.div_rem(&x_squared).0;
+        let term = power.div_rem(&(2 * k + 1).into()).0;
+        sum = if k % 2 == 1 {
+            &sum - &term
+        } else {
+            &sum + &term
+        };
+        k += 1;
+    }
+    sum
+}
+
+/// Computes e, multiplied by `scale`, using its series: e = 1/0! + 1/1! + 1/2! + ...
+fn e(scale: &BigInt) -> BigInt {
+    let mut term = scale.clone();
+    let mut sum = BigInt::default();
+    let mut k: i64 = 0;
+    while !term.is_zero() {
+        sum = &sum + &term;
+        k += 1;
+        term = term.div_rem(&k.into()).0;
+    }
+    sum
+}
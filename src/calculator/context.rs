@@ -0,0 +1,117 @@
+use std::collections::BTreeMap;
+
+use super::{constants, options::Options, types::Token, value::Value};
+
+/// The deepest that calls to user-defined functions can be nested, which
+/// stops runaway recursion such as `f(x) = f(x)`.
+pub const MAX_CALL_DEPTH: usize = 64;
+
+/// A function defined by the user, such as `f(x, y) = x^2 + y`. Its body is
+/// kept as written, so the names in it are looked up each time it is called
+/// rather than when it is defined.
+#[derive(Debug, Clone)]
+pub struct UserFunction {
+    pub parameters: Vec<String>,
+    pub body: Vec<Token>,
+}
+
+/// The state that carries over from one evaluation to the next: the options
+/// that control evaluation and the variables and functions that have been
+/// defined so far.
+#[derive(Debug, Clone, Default)]
+pub struct Context {
+    pub options: Options,
+    variables: BTreeMap<String, Value>,
+    functions: BTreeMap<String, UserFunction>,
+    /// The arguments of each user-defined function call in progress, with
+    /// the innermost call last.
+    frames: Vec<BTreeMap<String, Value>>,
+    warnings: Vec<String>,
+}
+
+impl Context {
+    pub fn new(options: Options) -> Self {
+        Context {
+            options,
+            ..Context::default()
+        }
+    }
+
+    /// Looks up a name as it appears in an expression. Inside a call to a
+    /// user-defined function, its parameters come first, followed by the variables.
+    pub fn resolve(&self, name: &str) -> Option<&Value> {
+        self.frames
+            .last()
+            .and_then(|frame| frame.get(name))
+            .or_else(|| self.variables.get(name))
+    }
+
+    /// Returns every variable along with its value, ordered by name.
+    pub fn variables(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.variables
+            .iter()
+            .map(|(name, value)| (name.as_str(), value))
+    }
+
+    /// Assigns a value to a variable, replacing any previous value. A variable
+    /// that has the same name as a constant hides that constant until the
+    /// variable is removed, so doing so records a warning.
+    pub fn assign(&mut self, name: String, value: Value) {
+        if constants::lookup(&name, &self.options).is_some() {
+            self.warnings.push(format!(
+                "variable {} shadows the constant of the same name",
+                name
+            ));
+        }
+        self.variables.insert(name, value);
+    }
+
+    /// Removes a variable, returning its value, or `None` if it has not been assigned.
+    pub fn remove_variable(&mut self, name: &str) -> Option<Value> {
+        self.variables.remove(name)
+    }
+
+    /// Returns a user-defined function, or `None` if it has not been defined.
+    pub fn function(&self, name: &str) -> Option<&UserFunction> {
+        self.functions.get(name)
+    }
+
+    /// Returns every user-defined function along with its parameters, ordered by name.
+    pub fn functions(&self) -> impl Iterator<Item = (&str, &[String])> {
+        self.functions
+            .iter()
+            .map(|(name, function)| (name.as_str(), function.parameters.as_slice()))
+    }
+
+    /// Defines a function, replacing any previous definition with the same name.
+    pub fn define(&mut self, name: String, function: UserFunction) {
+        self.functions.insert(name, function);
+    }
+
+    /// Removes a user-defined function, returning whether it was defined.
+    pub fn remove_function(&mut self, name: &str) -> bool {
+        self.functions.remove(name).is_some()
+    }
+
+    /// Returns the number of user-defined function calls in progress.
+    pub fn depth(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Enters a call to a user-defined function with its arguments bound to
+    /// the names of its parameters.
+    pub fn push_frame(&mut self, frame: BTreeMap<String, Value>) {
+        self.frames.push(frame);
+    }
+
+    /// Leaves the innermost call to a user-defined function.
+    pub fn pop_frame(&mut self) {
+        self.frames.pop();
+    }
+
+    /// Returns the warnings recorded since the last call, such as those about
+    /// variables shadowing constants.
+    pub fn take_warnings(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.warnings)
+    }
+}
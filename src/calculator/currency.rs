@@ -0,0 +1,103 @@
+use std::{collections::BTreeMap, fs};
+
+use super::{bigint::BigInt, rational::Rational};
+
+/// Exchange rates between currencies, loaded from a local file so that
+/// currency conversions work without a network connection.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rates {
+    /// The date that the rates are from, as written in the file.
+    pub date: String,
+    /// The amount of each currency that one unit of a common base currency
+    /// buys, keyed by currency code.
+    rates: BTreeMap<String, Rational>,
+}
+
+impl Rates {
+    /// Reads rates from a file. See `Rates::parse` for the format.
+    pub fn load(path: &str) -> Result<Rates, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|err| format!("could not read rates file {}: {}", path, err))?;
+        Rates::parse(&text).map_err(|err| format!("invalid rates file {}: {}", path, err))
+    }
+
+    /// Parses rates from the subset of TOML made up of a date and a table of
+    /// rates relative to a common base currency, whose own rate is 1:
+    ///
+    /// ```toml
+    /// date = "2024-05-01"
+    ///
+    /// [rates]
+    /// USD = 1
+    /// EUR = 0.92
+    /// ```
+    ///
+    /// Rates are positive decimals, which are kept exact.
+    pub fn parse(text: &str) -> Result<Rates, String> {
+        let mut date = None;
+        let mut rates = BTreeMap::new();
+        let mut table = "";
+        for (index, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(name) = line
+                .strip_prefix('[')
+                .and_then(|line| line.strip_suffix(']'))
+            {
+                table = name.trim();
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .map(|(key, value)| (key.trim(), value.trim()))
+                .ok_or_else(|| format!("expected key = value on line {}", index + 1))?;
+            match (table, key) {
+                ("", "date") => date = Some(value.trim_matches('"').to_string()),
+                ("rates", code)
+                    if !code.is_empty() && code.chars().all(|c| c.is_ascii_alphabetic()) =>
+                {
+                    let rate = Rational::from_decimal_str(value)
+                        .filter(|rate| *rate > Rational::from(BigInt::from(0)))
+                        .ok_or_else(|| {
+                            format!("invalid rate {} for {} on line {}", value, code, index + 1)
+                        })?;
+                    rates.insert(code.to_string(), rate);
+                }
+                _ => return Err(format!("unexpected key {} on line {}", key, index + 1)),
+            }
+        }
+        let date = date.ok_or("missing date")?;
+        if rates.is_empty() {
+            return Err(String::from("missing rates"));
+        }
+        Ok(Rates { date, rates })
+    }
+
+    /// Returns the amount of a currency that one unit of the base currency
+    /// buys, or `None` if there is no rate for it.
+    pub fn rate(&self, code: &str) -> Option<&Rational> {
+        self.rates.get(code)
+    }
+}
+
+/// Returns the number of digits after the decimal point in amounts of a
+/// currency, which is the size of its minor unit, such as 2 for the cents of
+/// the US dollar. Most currencies have a minor unit of a hundredth.
+pub fn minor_digits(code: &str) -> usize {
+    match code {
+        "BIF" | "CLP" | "DJF" | "GNF" | "ISK" | "JPY" | "KMF" | "KRW" | "PYG" | "RWF" | "UGX"
+        | "VND" | "VUV" | "XAF" | "XOF" | "XPF" => 0,
+        "BHD" | "IQD" | "JOD" | "KWD" | "LYD" | "OMR" | "TND" => 3,
+        _ => 2,
+    }
+}
+
+/// Rounds an amount to a number of digits after the decimal point, with ties
+/// going to the even digit, so that 0.125 becomes 0.12 and 0.135 becomes 0.14.
+pub fn round(amount: &Rational, digits: usize) -> Rational {
+    let scale = Rational::from(BigInt::from(10).pow(digits as u32));
+    let rounded = Rational::from((amount * &scale).round_half_even());
+    &rounded / &scale
+}
@@ -0,0 +1,256 @@
+use std::{
+    fmt::Display,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use super::{
+    bigint::BigInt,
+    options::Options,
+    rational::Rational,
+    types::{Error, Operator, Result},
+    units,
+    value::Value,
+};
+
+pub const SECONDS_PER_DAY: i64 = 86_400;
+
+/// A point in time on the calendar, such as `2026-10-18` or
+/// `2026-10-18T14:30`. It is kept as wall-clock time at the UTC offset from
+/// the options, so calendar calculations such as finding the weekday need no
+/// time zone, and it is precise to the second.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DateTime {
+    /// The number of seconds since `1970-01-01T00:00`.
+    seconds: i64,
+    /// Whether the time of day is part of the value, as it is for `now`,
+    /// or only the date, as it is for `today`.
+    has_time: bool,
+}
+
+impl DateTime {
+    /// Creates a point in time from a date on the proleptic Gregorian
+    /// calendar and the number of seconds since midnight. Returns `None` if
+    /// the date does not exist, such as `2026-02-30`.
+    pub fn new(year: i64, month: u32, day: u32, second: i64, has_time: bool) -> Option<DateTime> {
+        if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+            return None;
+        }
+        let seconds = days_from_civil(year, month, day)
+            .checked_mul(SECONDS_PER_DAY)?
+            .checked_add(second)?;
+        Some(DateTime { seconds, has_time })
+    }
+
+    /// Returns the current time at the UTC offset from the options.
+    pub fn now(options: &Options) -> DateTime {
+        let seconds = match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(elapsed) => elapsed.as_secs() as i64,
+            Err(before) => -(before.duration().as_secs() as i64),
+        };
+        DateTime {
+            seconds: seconds + options.utc_offset.seconds(),
+            has_time: true,
+        }
+    }
+
+    /// Returns the current date at the UTC offset from the options.
+    pub fn today(options: &Options) -> DateTime {
+        DateTime::now(options).date()
+    }
+
+    /// Returns the date of this point in time, without the time of day.
+    fn date(&self) -> DateTime {
+        DateTime {
+            seconds: self.days() * SECONDS_PER_DAY,
+            has_time: false,
+        }
+    }
+
+    /// Returns the number of days since `1970-01-01`.
+    fn days(&self) -> i64 {
+        self.seconds.div_euclid(SECONDS_PER_DAY)
+    }
+
+    /// Moves this point in time by a number of seconds. The time of day
+    /// becomes part of the value unless the move is by whole days. Returns
+    /// `None` if the result is out of range.
+    pub fn add_seconds(&self, seconds: i64) -> Option<DateTime> {
+        Some(DateTime {
+            seconds: self.seconds.checked_add(seconds)?,
+            has_time: self.has_time || seconds % SECONDS_PER_DAY != 0,
+        })
+    }
+
+    /// Returns the day of the week from 1 for Monday to 7 for Sunday, as in
+    /// ISO 8601.
+    pub fn weekday(&self) -> i64 {
+        // 1970-01-01 was a Thursday
+        (self.days() + 3).rem_euclid(7) + 1
+    }
+
+    /// Returns the ISO 8601 week number, from 1 to 53. Weeks start on
+    /// Monday, and week 1 is the week that holds the first Thursday of the
+    /// year, so the first days of January can belong to the last week of the
+    /// year before.
+    pub fn iso_week(&self) -> i64 {
+        let thursday = self.days() - self.weekday() + 4;
+        let (year, _, _) = civil_from_days(thursday);
+        (thursday - days_from_civil(year, 1, 1)) / 7 + 1
+    }
+
+    /// Moves this date by a number of business days, which are Monday to
+    /// Friday, skipping weekends, so one business day after a Friday, or
+    /// after the weekend that follows it, is the next Monday.
+    pub fn add_business_days(&self, days: i64) -> Option<DateTime> {
+        if days == 0 {
+            return Some(*self);
+        }
+        let step = days.signum();
+        // A weekend counts as the business day before it when moving forward
+        // and as the one after it when moving back, after which every 5
+        // business days make a whole week
+        let mut date = *self;
+        while date.weekday() > 5 {
+            date = date.add_seconds(-step * SECONDS_PER_DAY)?;
+        }
+        let weeks = (days / 5).checked_mul(7 * SECONDS_PER_DAY)?;
+        date = date.add_seconds(weeks)?;
+        for _ in 0..(days % 5).unsigned_abs() {
+            date = date.add_seconds(step * SECONDS_PER_DAY)?;
+            while date.weekday() > 5 {
+                date = date.add_seconds(step * SECONDS_PER_DAY)?;
+            }
+        }
+        Some(date)
+    }
+
+    /// Counts the business days from this date to another date, counting
+    /// both of them, so there are 5 from a Monday to the Friday after it. The
+    /// count is negative when the other date comes first.
+    pub fn business_days_until(&self, other: &DateTime) -> i64 {
+        if other < self {
+            return -other.business_days_until(self);
+        }
+        let (start, end) = (self.days(), other.days());
+        // Whole weeks hold 5 business days each, and the days left over are
+        // counted one at a time
+        let weeks = (end - start + 1) / 7;
+        let business_days = |day: i64| (day + 3).rem_euclid(7) < 5;
+        let rest = (start + weeks * 7..=end)
+            .filter(|day| business_days(*day))
+            .count() as i64;
+        weeks * 5 + rest
+    }
+}
+
+impl Display for DateTime {
+    /// Writes the date as `2026-10-18`, followed by the time of day as
+    /// `T14:30`, or `T14:30:15` when there are seconds, if it has one.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (year, month, day) = civil_from_days(self.days());
+        write!(f, "{:04}-{:02}-{:02}", year, month, day)?;
+        if !self.has_time {
+            return Ok(());
+        }
+        let second = self.seconds.rem_euclid(SECONDS_PER_DAY);
+        write!(f, "T{:02}:{:02}", second / 3600, second / 60 % 60)?;
+        match second % 60 {
+            0 => Ok(()),
+            second => write!(f, ":{:02}", second),
+        }
+    }
+}
+
+/// Applies `+` or `-` to two values, at least one of which is a date. A
+/// duration can be added to a date or subtracted from it, and subtracting two
+/// dates yields the time between them, in days when neither has a time of day
+/// and in hours otherwise.
+pub fn apply_binary_operator(operator: Operator, a: Value, b: Value) -> Result<Value> {
+    let out_of_range = || Error::InvalidExpression(String::from("date is out of range"));
+    match (operator, a, b) {
+        (Operator::Subtract, Value::Date(a), Value::Date(b)) => {
+            let seconds = BigInt::from(a.seconds) - BigInt::from(b.seconds);
+            Ok(Value::Quantity(if a.has_time || b.has_time {
+                units::quantity(Rational::new(seconds, BigInt::from(3600)), "h")
+            } else {
+                units::quantity(
+                    Rational::new(seconds, BigInt::from(SECONDS_PER_DAY)),
+                    "days",
+                )
+            }))
+        }
+        (Operator::Add | Operator::Subtract, Value::Date(date), Value::Quantity(duration))
+        | (Operator::Add, Value::Quantity(duration), Value::Date(date)) => {
+            let seconds = units::seconds(&duration).ok_or_else(|| {
+                Error::InvalidOperand(operator, Box::new(Value::Quantity(duration)))
+            })?;
+            let seconds = match operator {
+                Operator::Subtract => seconds.checked_neg().ok_or_else(out_of_range)?,
+                _ => seconds,
+            };
+            Ok(Value::Date(
+                date.add_seconds(seconds).ok_or_else(out_of_range)?,
+            ))
+        }
+        (_, Value::Date(_), b) => Err(Error::InvalidOperand(operator, Box::new(b))),
+        (_, a, _) => Err(Error::InvalidOperand(operator, Box::new(a))),
+    }
+}
+
+/// Converts a whole number of seconds to an `i64`, or returns `None` if it
+/// does not fit.
+pub fn to_i64(value: &BigInt) -> Option<i64> {
+    let magnitude = i64::try_from(value.abs().to_u64()?).ok()?;
+    Some(if value.is_negative() {
+        -magnitude
+    } else {
+        magnitude
+    })
+}
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Returns the number of days from `1970-01-01` to a date. This and
+/// `civil_from_days` follow Howard Hinnant's algorithms, which count in eras
+/// of 400 years that start in March, so that leap days fall at the end.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month = month as i64;
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Returns the year, month, and day of the date a number of days after
+/// `1970-01-01`.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
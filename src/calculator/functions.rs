@@ -0,0 +1,751 @@
+use super::{
+    bigint::BigInt,
+    complex::{self, Complex},
+    date::{self, DateTime},
+    modular,
+    number::Number,
+    options::{AngleMode, Options},
+    rational::Rational,
+    types::{Arity, Error, Operator, Result},
+    value::Value,
+    Calculator,
+};
+
+/// A function that is built into the calculator, such as `sqrt` or `max`.
+pub struct Builtin {
+    pub name: &'static str,
+    pub arity: Arity,
+    apply: Apply,
+}
+
+/// The implementation of a built-in function, which determines the kinds of
+/// arguments that it accepts.
+enum Apply {
+    /// Takes real numbers only.
+    Real(fn(Vec<Number>, &Options) -> Result<Number>),
+    /// Takes real numbers only, but may return a complex number.
+    RealToComplex(fn(Vec<Number>, &Options) -> Result<Value>),
+    /// Takes a single number, which may be complex. Real numbers are passed
+    /// as complex numbers with an imaginary part of zero.
+    Complex(fn(Complex, &Options) -> Result<Value>),
+    /// Takes a date, followed by any other arguments, which the function
+    /// checks itself.
+    Date(fn(DateTime, Vec<Value>) -> Result<Value>),
+}
+
+impl Builtin {
+    /// Applies this function to a list of arguments. The number of arguments
+    /// is assumed to have been checked against the arity of the function.
+    pub fn call(&self, arguments: Vec<Value>, options: &Options) -> Result<Value> {
+        let invalid = |argument| Error::InvalidArgument(self.name.into(), Box::new(argument));
+        let real = |arguments: Vec<Value>| {
+            arguments
+                .into_iter()
+                .map(|argument| match argument {
+                    Value::Number(number) => Ok(number),
+                    argument => Err(invalid(argument)),
+                })
+                .collect::<Result<Vec<Number>>>()
+        };
+        match self.apply {
+            Apply::Real(apply) => apply(real(arguments)?, options).map(Value::Number),
+            Apply::RealToComplex(apply) => apply(real(arguments)?, options),
+            Apply::Complex(apply) => match first(arguments) {
+                Value::Number(number) => apply(Complex::from(number), options),
+                Value::Complex(complex) => apply(complex, options),
+                argument => Err(invalid(argument)),
+            },
+            Apply::Date(apply) => {
+                let mut arguments = arguments.into_iter();
+                match arguments.next() {
+                    Some(Value::Date(date)) => apply(date, arguments.collect()),
+                    Some(argument) => Err(invalid(argument)),
+                    None => unreachable!(), // Guaranteed to exist because of arity
+                }
+            }
+        }
+    }
+}
+
+const BUILTINS: &[Builtin] = &[
+    Builtin {
+        name: "abs",
+        arity: Arity::Exactly(1),
+        apply: Apply::Complex(complex_abs),
+    },
+    Builtin {
+        name: "min",
+        arity: Arity::AtLeast(1),
+        apply: Apply::Real(min),
+    },
+    Builtin {
+        name: "max",
+        arity: Arity::AtLeast(1),
+        apply: Apply::Real(max),
+    },
+    Builtin {
+        name: "sqrt",
+        arity: Arity::Exactly(1),
+        apply: Apply::Complex(complex_sqrt),
+    },
+    Builtin {
+        name: "pow",
+        arity: Arity::Exactly(2),
+        apply: Apply::RealToComplex(pow),
+    },
+    Builtin {
+        name: "floor",
+        arity: Arity::Exactly(1),
+        apply: Apply::Real(floor),
+    },
+    Builtin {
+        name: "ceil",
+        arity: Arity::Exactly(1),
+        apply: Apply::Real(ceil),
+    },
+    Builtin {
+        name: "round",
+        arity: Arity::Exactly(1),
+        apply: Apply::Real(round),
+    },
+    Builtin {
+        name: "log",
+        arity: Arity::Between(1, 2),
+        apply: Apply::RealToComplex(log),
+    },
+    Builtin {
+        name: "ln",
+        arity: Arity::Exactly(1),
+        apply: Apply::Complex(complex_ln),
+    },
+    Builtin {
+        name: "exp",
+        arity: Arity::Exactly(1),
+        apply: Apply::Complex(complex_exp),
+    },
+    Builtin {
+        name: "sin",
+        arity: Arity::Exactly(1),
+        apply: Apply::Real(sin),
+    },
+    Builtin {
+        name: "cos",
+        arity: Arity::Exactly(1),
+        apply: Apply::Real(cos),
+    },
+    Builtin {
+        name: "tan",
+        arity: Arity::Exactly(1),
+        apply: Apply::Real(tan),
+    },
+    Builtin {
+        name: "asin",
+        arity: Arity::Exactly(1),
+        apply: Apply::Real(asin),
+    },
+    Builtin {
+        name: "acos",
+        arity: Arity::Exactly(1),
+        apply: Apply::Real(acos),
+    },
+    Builtin {
+        name: "atan",
+        arity: Arity::Exactly(1),
+        apply: Apply::Real(atan),
+    },
+    Builtin {
+        name: "sinh",
+        arity: Arity::Exactly(1),
+        apply: Apply::Real(sinh),
+    },
+    Builtin {
+        name: "cosh",
+        arity: Arity::Exactly(1),
+        apply: Apply::Real(cosh),
+    },
+    Builtin {
+        name: "tanh",
+        arity: Arity::Exactly(1),
+        apply: Apply::Real(tanh),
+    },
+    Builtin {
+        name: "asinh",
+        arity: Arity::Exactly(1),
+        apply: Apply::Real(asinh),
+    },
+    Builtin {
+        name: "acosh",
+        arity: Arity::Exactly(1),
+        apply: Apply::Real(acosh),
+    },
+    Builtin {
+        name: "atanh",
+        arity: Arity::Exactly(1),
+        apply: Apply::Real(atanh),
+    },
+    Builtin {
+        name: "arg",
+        arity: Arity::Exactly(1),
+        apply: Apply::Complex(arg),
+    },
+    Builtin {
+        name: "conj",
+        arity: Arity::Exactly(1),
+        apply: Apply::Complex(conj),
+    },
+    Builtin {
+        name: "re",
+        arity: Arity::Exactly(1),
+        apply: Apply::Complex(re),
+    },
+    Builtin {
+        name: "im",
+        arity: Arity::Exactly(1),
+        apply: Apply::Complex(im),
+    },
+    Builtin {
+        name: "weekday",
+        arity: Arity::Exactly(1),
+        apply: Apply::Date(weekday),
+    },
+    Builtin {
+        name: "week",
+        arity: Arity::Exactly(1),
+        apply: Apply::Date(week),
+    },
+    Builtin {
+        name: "workday",
+        arity: Arity::Exactly(2),
+        apply: Apply::Date(workday),
+    },
+    Builtin {
+        name: "networkdays",
+        arity: Arity::Exactly(2),
+        apply: Apply::Date(networkdays),
+    },
+    Builtin {
+        name: "powmod",
+        arity: Arity::Exactly(3),
+        apply: Apply::Real(powmod),
+    },
+    Builtin {
+        name: "modinv",
+        arity: Arity::Exactly(2),
+        apply: Apply::Real(modinv),
+    },
+    Builtin {
+        name: "crt",
+        arity: Arity::AtLeast(2),
+        apply: Apply::Real(crt),
+    },
+];
+
+/// Finds a built-in function by name.
+pub fn lookup(name: &str) -> Option<&'static Builtin> {
+    BUILTINS.iter().find(|builtin| builtin.name == name)
+}
+
+fn abs(arguments: Vec<Number>, options: &Options) -> Result<Number> {
+    let x = first(arguments);
+    if x < Number::from(0) {
+        Calculator::negate(x, options)
+    } else {
+        Ok(x)
+    }
+}
+
+/// Takes the distance of a number from zero. It is exact for a complex number
+/// when the sum of the squares of its parts is a perfect square, as for `3+4i`.
+fn complex_abs(z: Complex, options: &Options) -> Result<Value> {
+    if z.im.is_zero() {
+        return abs(vec![z.re], options).map(Value::Number);
+    }
+    let square = Calculator::add(
+        Calculator::multiply(z.re.clone(), z.re, options)?,
+        Calculator::multiply(z.im.clone(), z.im, options)?,
+        options,
+    )?;
+    sqrt(vec![square], options).map(Value::Number)
+}
+
+fn min(arguments: Vec<Number>, _: &Options) -> Result<Number> {
+    Ok(extreme(arguments, |candidate, best| candidate < best))
+}
+
+fn max(arguments: Vec<Number>, _: &Options) -> Result<Number> {
+    Ok(extreme(arguments, |candidate, best| candidate > best))
+}
+
+/// Picks the argument that is preferred over all others. Any argument that
+/// is not a number makes the whole result not a number.
+fn extreme(arguments: Vec<Number>, prefer: fn(&Number, &Number) -> bool) -> Number {
+    let mut arguments = arguments.into_iter();
+    let mut best = arguments.next().unwrap(); // Guaranteed to exist because of arity
+    for candidate in arguments {
+        if matches!(candidate, Number::Float(float) if float.is_nan()) || prefer(&candidate, &best)
+        {
+            best = candidate;
+        }
+        if matches!(best, Number::Float(float) if float.is_nan()) {
+            break;
+        }
+    }
+    best
+}
+
+/// Takes the square root of a number. The root of a perfect square, or of a
+/// rational whose numerator and denominator are both perfect squares, is exact.
+fn sqrt(arguments: Vec<Number>, _: &Options) -> Result<Number> {
+    let x = first(arguments);
+    if x < Number::from(0) {
+        return Err(Error::InvalidArgument("sqrt".into(), Box::new(x.into())));
+    }
+    match &x {
+        Number::Integer(integer) => {
+            if let Some(root) = exact_sqrt(integer) {
+                return Ok(Number::Integer(root));
+            }
+        }
+        Number::Rational(rational) => {
+            if let (Some(numerator), Some(denominator)) = (
+                exact_sqrt(rational.numerator()),
+                exact_sqrt(rational.denominator()),
+            ) {
+                return Ok(Number::from(Rational::new(numerator, denominator)));
+            }
+        }
+        Number::Float(_) => (),
+    }
+    Ok(Number::Float(x.to_f64().sqrt()))
+}
+
+/// Takes the principal square root of a number. The root of a negative real
+/// number is imaginary, so `sqrt(-4)` is `2i`, and it is exact whenever the
+/// root of the real number's magnitude is.
+fn complex_sqrt(z: Complex, options: &Options) -> Result<Value> {
+    if z.im.is_zero() {
+        if z.re < Number::from(0) {
+            let root = sqrt(vec![Calculator::negate(z.re, options)?], options)?;
+            return Ok(Value::from(Complex::new(Number::from(0), root)));
+        }
+        return sqrt(vec![z.re], options).map(Value::Number);
+    }
+    let (modulus, re) = (z.modulus(), z.re.to_f64());
+    Ok(Value::from(Complex::new(
+        Number::Float(((modulus + re) / 2.0).sqrt()),
+        Number::Float(((modulus - re) / 2.0).sqrt().copysign(z.im.to_f64())),
+    )))
+}
+
+fn exact_sqrt(integer: &BigInt) -> Option<BigInt> {
+    let root = integer.isqrt();
+    (&(&root * &root) == integer).then_some(root)
+}
+
+/// Raises a number to a power, which is complex for a negative base and an
+/// exponent that is not a whole number, like with `^`.
+fn pow(arguments: Vec<Number>, options: &Options) -> Result<Value> {
+    let mut arguments = arguments.into_iter();
+    let base = arguments.next().unwrap(); // Guaranteed to exist because of arity
+    let exponent = arguments.next().unwrap(); // Guaranteed to exist because of arity
+    if complex::is_complex_power(&base, &exponent) {
+        return complex::apply_binary_operator(
+            Operator::Power,
+            Complex::from(base),
+            Complex::from(exponent),
+            options,
+        );
+    }
+    Calculator::power(base, exponent, options).map(Value::Number)
+}
+
+fn floor(arguments: Vec<Number>, _: &Options) -> Result<Number> {
+    Ok(match first(arguments) {
+        Number::Integer(integer) => Number::Integer(integer),
+        Number::Rational(rational) => Number::Integer(rational.floor()),
+        Number::Float(float) => integer_from_float(float.floor()),
+    })
+}
+
+fn ceil(arguments: Vec<Number>, _: &Options) -> Result<Number> {
+    Ok(match first(arguments) {
+        Number::Integer(integer) => Number::Integer(integer),
+        Number::Rational(rational) => Number::Integer(rational.ceil()),
+        Number::Float(float) => integer_from_float(float.ceil()),
+    })
+}
+
+/// Rounds a number to the nearest integer, rounding halfway cases away from zero.
+fn round(arguments: Vec<Number>, _: &Options) -> Result<Number> {
+    Ok(match first(arguments) {
+        Number::Integer(integer) => Number::Integer(integer),
+        Number::Rational(rational) => Number::Integer(rational.round()),
+        Number::Float(float) => integer_from_float(float.round()),
+    })
+}
+
+/// Converts an integral float to an integer, leaving infinities and values
+/// that are not a number as they are.
+fn integer_from_float(float: f64) -> Number {
+    BigInt::from_f64(float).map_or(Number::Float(float), Number::Integer)
+}
+
+/// Takes the logarithm of a number, in base 10 unless a base is given. The
+/// logarithm of a negative number is complex, like its natural logarithm, so
+/// `log(-10)` is 1 plus π divided by the natural logarithm of 10 times i.
+fn log(arguments: Vec<Number>, _: &Options) -> Result<Value> {
+    let mut arguments = arguments.into_iter();
+    let x = arguments.next().unwrap(); // Guaranteed to exist because of arity
+    let base_value = match arguments.next() {
+        None => 10.0,
+        Some(base) => match positive_argument("log", base.clone())? {
+            1.0 => return Err(Error::InvalidArgument("log".into(), Box::new(base.into()))),
+            base_value => base_value,
+        },
+    };
+    if x < Number::from(0) {
+        let ln_base = base_value.ln();
+        return Ok(Value::from(Complex::new(
+            Number::Float(x.to_f64().abs().ln() / ln_base),
+            Number::Float(std::f64::consts::PI / ln_base),
+        )));
+    }
+    let x = positive_argument("log", x)?;
+    let result = if base_value == 2.0 {
+        x.log2()
+    } else if base_value == 10.0 {
+        x.log10()
+    } else {
+        x.ln() / base_value.ln()
+    };
+    Ok(Value::Number(Number::Float(result)))
+}
+
+fn ln(arguments: Vec<Number>, _: &Options) -> Result<Number> {
+    Ok(Number::Float(
+        positive_argument("ln", first(arguments))?.ln(),
+    ))
+}
+
+/// Takes the principal natural logarithm of a number. The logarithm of a
+/// negative or complex number has the angle of the number, in radians, as its
+/// imaginary part, so `ln(-1)` is πi.
+fn complex_ln(z: Complex, options: &Options) -> Result<Value> {
+    if z.im.is_zero() && z.re >= Number::from(0) {
+        return ln(vec![z.re], options).map(Value::Number);
+    }
+    Ok(Value::from(Complex::new(
+        Number::Float(z.modulus().ln()),
+        Number::Float(z.argument()),
+    )))
+}
+
+fn exp(arguments: Vec<Number>, _: &Options) -> Result<Number> {
+    Ok(Number::Float(first(arguments).to_f64().exp()))
+}
+
+/// Raises e to the power of a number. The imaginary part of the power is an
+/// angle in radians, so `exp(pi * i)` is -1 up to rounding error.
+fn complex_exp(z: Complex, options: &Options) -> Result<Value> {
+    if z.im.is_zero() {
+        return exp(vec![z.re], options).map(Value::Number);
+    }
+    let (magnitude, angle) = (z.re.to_f64().exp(), z.im.to_f64());
+    Ok(Value::from(Complex::new(
+        Number::Float(magnitude * angle.cos()),
+        Number::Float(magnitude * angle.sin()),
+    )))
+}
+
+/// Returns the angle of a number from the positive real axis, in the unit
+/// set by the angle mode.
+fn arg(z: Complex, options: &Options) -> Result<Value> {
+    Ok(Value::Number(Number::Float(
+        options.angle.angle_from_radians(z.argument()),
+    )))
+}
+
+/// Returns the complex conjugate of a number, which has the opposite imaginary part.
+fn conj(z: Complex, options: &Options) -> Result<Value> {
+    Ok(Value::from(Complex::new(
+        z.re,
+        Calculator::negate(z.im, options)?,
+    )))
+}
+
+fn re(z: Complex, _: &Options) -> Result<Value> {
+    Ok(Value::Number(z.re))
+}
+
+fn im(z: Complex, _: &Options) -> Result<Value> {
+    Ok(Value::Number(z.im))
+}
+
+pub fn sin(arguments: Vec<Number>, options: &Options) -> Result<Number> {
+    let angle = first(arguments).to_f64();
+    Ok(Number::Float(
+        match special_angle(angle, options.angle).and_then(exact_sine) {
+            Some(sine) => sine,
+            None => options.angle.angle_to_radians(angle).sin(),
+        },
+    ))
+}
+
+pub fn cos(arguments: Vec<Number>, options: &Options) -> Result<Number> {
+    let angle = first(arguments).to_f64();
+    // The cosine of an angle is the sine of the angle a quarter turn further
+    let cosine = special_angle(angle, options.angle).and_then(|parts| exact_sine((parts + 6) % 24));
+    Ok(Number::Float(match cosine {
+        Some(cosine) => cosine,
+        None => options.angle.angle_to_radians(angle).cos(),
+    }))
+}
+
+fn tan(arguments: Vec<Number>, options: &Options) -> Result<Number> {
+    let angle = first(arguments);
+    let value = angle.to_f64();
+    Ok(Number::Float(match special_angle(value, options.angle) {
+        Some(0 | 12) => 0.0,
+        Some(3 | 15) => 1.0,
+        Some(9 | 21) => -1.0,
+        Some(6 | 18) => return Err(Error::InvalidArgument("tan".into(), Box::new(angle.into()))),
+        _ => options.angle.angle_to_radians(value).tan(),
+    }))
+}
+
+/// Returns the number of twenty-fourths of a turn in an angle, modulo a full
+/// turn, when it is a whole number of them. Trigonometric functions have
+/// exact values at some of these angles, which floating point arithmetic
+/// would otherwise miss, such as the sine of 180 degrees coming out as a tiny
+/// nonzero number or the tangent of 45 degrees as slightly less than 1.
+fn special_angle(angle: f64, mode: AngleMode) -> Option<u8> {
+    let parts = angle.rem_euclid(mode.full_turn()) * 24.0 / mode.full_turn();
+    (parts.fract() == 0.0).then_some(parts as u8 % 24)
+}
+
+/// Returns the sine of an angle given in twenty-fourths of a turn when it is
+/// a rational number, which is the case for multiples of 30 and 90 degrees.
+fn exact_sine(parts: u8) -> Option<f64> {
+    match parts {
+        0 | 12 => Some(0.0),
+        2 | 10 => Some(0.5),
+        6 => Some(1.0),
+        14 | 22 => Some(-0.5),
+        18 => Some(-1.0),
+        _ => None,
+    }
+}
+
+/// Converts the result of an inverse trigonometric function to the unit set
+/// by the angle mode. Results that are a whole number of twenty-fourths of a
+/// turn, such as the 30 degrees of `asin(0.5)`, are given exactly in degrees
+/// and gradians rather than converted from radians with a rounding error.
+fn inverse_angle(parts: Option<i8>, radians: f64, mode: AngleMode) -> f64 {
+    match (parts, mode) {
+        (Some(parts), AngleMode::Degrees | AngleMode::Gradians) => {
+            mode.full_turn() * parts as f64 / 24.0
+        }
+        _ => mode.angle_from_radians(radians),
+    }
+}
+
+/// Returns the arcsine of a number in twenty-fourths of a turn when it is a
+/// whole number of them.
+fn exact_arcsine(x: f64) -> Option<i8> {
+    match x {
+        -1.0 => Some(-6),
+        -0.5 => Some(-2),
+        0.0 => Some(0),
+        0.5 => Some(2),
+        1.0 => Some(6),
+        _ => None,
+    }
+}
+
+fn asin(arguments: Vec<Number>, options: &Options) -> Result<Number> {
+    let x = bounded_argument("asin", first(arguments), -1.0, 1.0)?;
+    Ok(Number::Float(inverse_angle(
+        exact_arcsine(x),
+        x.asin(),
+        options.angle,
+    )))
+}
+
+fn acos(arguments: Vec<Number>, options: &Options) -> Result<Number> {
+    let x = bounded_argument("acos", first(arguments), -1.0, 1.0)?;
+    // The arccosine is a quarter turn less the arcsine
+    let parts = exact_arcsine(x).map(|parts| 6 - parts);
+    Ok(Number::Float(inverse_angle(parts, x.acos(), options.angle)))
+}
+
+fn atan(arguments: Vec<Number>, options: &Options) -> Result<Number> {
+    let x = first(arguments).to_f64();
+    let parts = match x {
+        -1.0 => Some(-3),
+        0.0 => Some(0),
+        1.0 => Some(3),
+        _ => None,
+    };
+    Ok(Number::Float(inverse_angle(parts, x.atan(), options.angle)))
+}
+
+fn sinh(arguments: Vec<Number>, _: &Options) -> Result<Number> {
+    Ok(Number::Float(first(arguments).to_f64().sinh()))
+}
+
+fn cosh(arguments: Vec<Number>, _: &Options) -> Result<Number> {
+    Ok(Number::Float(first(arguments).to_f64().cosh()))
+}
+
+fn tanh(arguments: Vec<Number>, _: &Options) -> Result<Number> {
+    Ok(Number::Float(first(arguments).to_f64().tanh()))
+}
+
+fn asinh(arguments: Vec<Number>, _: &Options) -> Result<Number> {
+    Ok(Number::Float(first(arguments).to_f64().asinh()))
+}
+
+fn acosh(arguments: Vec<Number>, _: &Options) -> Result<Number> {
+    let x = bounded_argument("acosh", first(arguments), 1.0, f64::INFINITY)?;
+    Ok(Number::Float(x.acosh()))
+}
+
+fn atanh(arguments: Vec<Number>, _: &Options) -> Result<Number> {
+    let x = bounded_argument("atanh", first(arguments), -1.0, 1.0)?;
+    if x.abs() == 1.0 {
+        return Err(Error::InvalidArgument(
+            "atanh".into(),
+            Box::new(Number::Float(x).into()),
+        ));
+    }
+    Ok(Number::Float(x.atanh()))
+}
+
+/// Returns the day of the week of a date, from 1 for Monday to 7 for Sunday.
+fn weekday(date: DateTime, _: Vec<Value>) -> Result<Value> {
+    Ok(Value::Number(Number::Integer(date.weekday().into())))
+}
+
+/// Returns the ISO 8601 week number of a date.
+fn week(date: DateTime, _: Vec<Value>) -> Result<Value> {
+    Ok(Value::Number(Number::Integer(date.iso_week().into())))
+}
+
+/// Moves a date by a whole number of business days, which may be negative.
+fn workday(date: DateTime, arguments: Vec<Value>) -> Result<Value> {
+    let days = first(arguments);
+    let moved = match &days {
+        Value::Number(Number::Integer(days)) => date::to_i64(days)
+            .and_then(|days| date.add_business_days(days))
+            .map(Value::Date),
+        _ => None,
+    };
+    moved.ok_or_else(|| Error::InvalidArgument("workday".into(), Box::new(days)))
+}
+
+/// Counts the business days from one date to another, including both.
+fn networkdays(start: DateTime, arguments: Vec<Value>) -> Result<Value> {
+    match first(arguments) {
+        Value::Date(end) => Ok(Value::Number(Number::Integer(
+            start.business_days_until(&end).into(),
+        ))),
+        argument => Err(Error::InvalidArgument(
+            "networkdays".into(),
+            Box::new(argument),
+        )),
+    }
+}
+
+/// Raises an integer to a power modulo a positive modulus, as in `powmod(a, e, m)`.
+fn powmod(arguments: Vec<Number>, _: &Options) -> Result<Number> {
+    let Ok([base, exponent, modulus]) =
+        <[BigInt; 3]>::try_from(integer_arguments("powmod", arguments)?)
+    else {
+        unreachable!() // Guaranteed to have three arguments because of arity
+    };
+    let modulus = modulus_argument("powmod", modulus)?;
+    Ok(Number::Integer(modular::power(&base, &exponent, &modulus)?))
+}
+
+/// Returns the inverse of an integer modulo a positive modulus, as in `modinv(a, m)`.
+fn modinv(arguments: Vec<Number>, _: &Options) -> Result<Number> {
+    let Ok([a, modulus]) = <[BigInt; 2]>::try_from(integer_arguments("modinv", arguments)?) else {
+        unreachable!() // Guaranteed to have two arguments because of arity
+    };
+    let modulus = modulus_argument("modinv", modulus)?;
+    Ok(Number::Integer(modular::inverse(&a, &modulus)?))
+}
+
+/// Solves a system of congruences given as pairs of a residue and a modulus,
+/// as in `crt(2, 3, 3, 5)` for x = 2 mod 3 and x = 3 mod 5.
+fn crt(arguments: Vec<Number>, _: &Options) -> Result<Number> {
+    if !arguments.len().is_multiple_of(2) {
+        return Err(Error::InvalidArgument(
+            "crt".into(),
+            Box::new(arguments.into_iter().next_back().unwrap().into()), // Guaranteed to exist because of arity
+        ));
+    }
+    let mut arguments = integer_arguments("crt", arguments)?.into_iter();
+    let mut congruences = vec![];
+    while let (Some(residue), Some(modulus)) = (arguments.next(), arguments.next()) {
+        congruences.push((residue, modulus_argument("crt", modulus)?));
+    }
+    Ok(Number::Integer(modular::chinese_remainder(&congruences)?))
+}
+
+/// Extracts integers from the arguments, failing on the first argument that
+/// is not an integer.
+fn integer_arguments(name: &str, arguments: Vec<Number>) -> Result<Vec<BigInt>> {
+    arguments
+        .into_iter()
+        .map(|argument| match argument {
+            Number::Integer(integer) => Ok(integer),
+            argument => Err(Error::InvalidArgument(
+                name.into(),
+                Box::new(argument.into()),
+            )),
+        })
+        .collect()
+}
+
+/// Fails unless a modulus is a positive integer.
+fn modulus_argument(name: &str, modulus: BigInt) -> Result<BigInt> {
+    if modulus > BigInt::from(0) {
+        Ok(modulus)
+    } else {
+        Err(Error::InvalidArgument(
+            name.into(),
+            Box::new(Number::Integer(modulus).into()),
+        ))
+    }
+}
+
+fn first<T>(arguments: Vec<T>) -> T {
+    arguments.into_iter().next().unwrap() // Guaranteed to exist because of arity
+}
+
+/// Converts an argument to a float, failing if it is not strictly positive.
+fn positive_argument(name: &str, argument: Number) -> Result<f64> {
+    if argument > Number::from(0) {
+        Ok(argument.to_f64())
+    } else {
+        Err(Error::InvalidArgument(
+            name.into(),
+            Box::new(argument.into()),
+        ))
+    }
+}
+
+/// Converts an argument to a float, failing if it falls outside of the
+/// inclusive range from `min` to `max`.
+fn bounded_argument(name: &str, argument: Number, min: f64, max: f64) -> Result<f64> {
+    let value = argument.to_f64();
+    if (min..=max).contains(&value) {
+        Ok(value)
+    } else {
+        Err(Error::InvalidArgument(
+            name.into(),
+            Box::new(argument.into()),
+        ))
+    }
+}
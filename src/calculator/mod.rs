@@ -1,8 +1,35 @@
-mod types;
+use std::{
+    cmp::Ordering,
+    f64::consts::{LN_2, PI},
+};
 
-use std::collections::HashMap;
+mod bigint;
+mod complex;
+mod constants;
+mod context;
+mod currency;
+mod date;
+mod functions;
+mod modular;
+mod number;
+mod options;
+mod rational;
+mod types;
+mod units;
+mod value;
 
-use self::types::{Error, Operator, Result, Token};
+pub use self::bigint::BigInt;
+pub use self::complex::Complex;
+pub use self::context::Context;
+use self::context::{UserFunction, MAX_CALL_DEPTH};
+pub use self::currency::Rates;
+use self::date::DateTime;
+pub use self::number::Number;
+use self::options::{ImplicitMultiplication, BOUNDED_INTEGER_BITS, MAX_INTEGER_BITS};
+pub use self::options::{NumericMode, Options, OverflowPolicy, MAX_DIGITS};
+use self::rational::Rational;
+use self::types::{Arity, Associativity, Error, Operand, Operator, Result, Token};
+pub use self::value::Value;
 
 pub struct Calculator;
 
@@ -12,15 +39,265 @@ impl Calculator {
     /// in case of tokenization, validation, or computation failures.
     /// It is capable of handling expressions that contain the following elements;
     /// all other elements are unsupported at this time:
-    /// * Integers
-    /// * Operands (binary): +, -, *, /
+    /// * Integers, of arbitrary size, in decimal or with a 0x, 0o, or 0b prefix
+    ///   for hexadecimal, octal, or binary, such as 0xff or 0b1010_0101
+    /// * Decimals, such as 3.5, .5, or 5.
+    /// * Operands (binary): +, -, *, /, ^ (or **), // (floor division),
+    ///   % (remainder), mod (Euclidean modulo)
+    /// * Bitwise operands on integers: & (and), | (or), xor, ~ (not, unary),
+    ///   << (shift left), >> (arithmetic shift right), >>> (logical shift right)
+    /// * Operands (unary): -, +
+    /// * Operands (postfix): ! (factorial), !! (double factorial), and % (percent),
+    ///   which bind more tightly than any other operator, so 2^3! is 2^6. A % is
+    ///   a remainder instead when an operand follows it, such as in 7 % 3
+    /// * Percentages, which work like fractions, so 20% * 80 is 16, except that
+    ///   adding a percentage to a number, or subtracting one from it, is relative
+    ///   to that number, so 200 + 15% is 230 and 200 - 15% is 170. Adding or
+    ///   subtracting two percentages yields a percentage, and of multiplies
+    ///   like *, so 20% of 80 is 16
     /// * Parentheses
-    pub fn evaluate(input: &str) -> Result<isize> {
-        let infix_expression = Calculator::tokenize_expression(input)?;
+    /// * Function calls with comma-separated arguments, such as sqrt(16) or
+    ///   max(3, 7, 2), using the functions abs, min, max, sqrt, pow, floor,
+    ///   ceil, round, log (base 10, or with a base as a second argument), ln, and exp
+    /// * Constants pi, tau, e, phi (the golden ratio), and inf, such as 2 * pi * 5
+    /// * Trigonometric functions sin, cos, and tan, which take angles in the
+    ///   unit set by the angle mode, their inverses asin, acos, and atan, which
+    ///   return angles in that unit, and the hyperbolic functions sinh, cosh,
+    ///   tanh, asinh, acosh, and atanh
+    /// * Variables, which are assigned with name = expression, such as rate = 0.07,
+    ///   and can then be used like constants
+    /// * User-defined functions, which are defined with name(parameters) = expression,
+    ///   such as f(x, y) = x^2 + y, and called like built-in functions
+    /// * Implicit multiplication, such as 2(3 + 4), (1 + 2)(3 + 4), or 3pi, when
+    ///   it is enabled in the options. A name directly followed by parentheses
+    ///   is always a function call, so x(2) does not multiply x by 2
+    /// * Multiple statements separated by semicolons, such as rate = 0.07; 2 * rate,
+    ///   which are evaluated in order and yield the result of the last one
+    /// * Complex numbers, written with the imaginary unit i, such as (1+2i)*(3-i),
+    ///   or in polar form with ∠, such as 2∠90, with the angle in the unit set by
+    ///   the angle mode. The functions abs, sqrt, ln, and exp accept complex
+    ///   numbers, sqrt and ln of negative numbers yield them, and arg, conj, re,
+    ///   and im take them apart
+    /// * Comparisons ==, !=, <, <=, >, and >=, which yield the booleans true
+    ///   and false, the logical operators and, or, and not, which take
+    ///   booleans, and conditional expressions, such as x < 0 ? -x : x, which
+    ///   only evaluate the value that is chosen
+    /// * Quantities with units, such as 5 km + 300 m or 9.81 m/s^2 * 70 kg, from
+    ///   a built-in table of SI and common units. A unit directly after a
+    ///   number binds to it more tightly than any operator but ^. Sums and
+    ///   comparisons need the same dimension, sums take the unit on the left,
+    ///   and to or in converts to another unit, such as 60 mph to km/h
+    /// * Amounts of money, such as 100 USD + 50 EUR in GBP, when the options
+    ///   hold exchange rates. Amounts are exact and are displayed rounded half
+    ///   to even to the minor unit of their currency
+    /// * Data sizes and rates, such as 1.5 TiB / 100 MB/s, in bits and bytes
+    ///   with SI and IEC prefixes. A unit divided by another with no spaces
+    ///   around the /, such as MB/s, is a single unit, and results are shown
+    ///   with the largest prefix that leaves at least 1 of the unit
+    /// * Dates, such as 2026-10-18 or 2026-10-18T14:30, and the constants now
+    ///   and today at the UTC offset from the options. Durations, such as
+    ///   90 days, 3h 20m, or a time of day like 14:30, can be added to dates
+    ///   and subtracted from them, and the difference of two dates is a
+    ///   duration. The functions weekday, week (the ISO week), workday, and
+    ///   networkdays work with the calendar
+    /// * Modular arithmetic, when the options hold a modulus. Sums,
+    ///   differences, products, and powers are reduced by it, division
+    ///   multiplies by the modular inverse of the divisor, and results are
+    ///   residues. The functions powmod, modinv, and crt (the Chinese remainder
+    ///   theorem) work in any mode
+    ///
+    /// Integer results are exact. Division yields an integer when the
+    /// quotient is exact. Otherwise, the numeric mode from the options decides
+    /// whether the result is a float or an exact rational.
+    ///
+    /// The context holds the options, which control how the computation is
+    /// carried out, such as what happens when an integer result overflows. It
+    /// also holds the variables and functions, which assignments and
+    /// definitions add to. The result is a number, or a boolean when the last
+    /// operator is a comparison or logical operator. There is no result when
+    /// the last statement defines a function.
+    pub fn evaluate(input: &str, context: &mut Context) -> Result<Option<Value>> {
+        let tokens = Calculator::tokenize_expression(input)?;
+        let mut result = None;
+        for statement in tokens.split(|token| *token == Token::Semicolon) {
+            if !statement.is_empty() {
+                result = Some(Calculator::evaluate_statement(statement.to_vec(), context)?);
+            }
+        }
+        result.ok_or(Error::ZeroLengthExpression)
+    }
+
+    /// Evaluates a single statement, which is either an expression, an
+    /// assignment of an expression to a variable, or a function definition.
+    fn evaluate_statement(
+        mut infix_expression: Vec<Token>,
+        context: &mut Context,
+    ) -> Result<Option<Value>> {
+        infix_expression = Calculator::attach_units(infix_expression, context);
+        if context.options.implicit_multiplication != ImplicitMultiplication::Off {
+            infix_expression = Calculator::insert_implicit_multiplication(
+                infix_expression,
+                context.options.implicit_multiplication,
+            );
+        }
+        if let Some(definition) = Calculator::parse_function_definition(&infix_expression) {
+            let (name, function) = definition?;
+            context.define(name, function);
+            return Ok(None);
+        }
+        let variable = match infix_expression.as_slice() {
+            [Token::Identifier(name, _), Token::Assign, ..] => Some(name.clone()),
+            _ => None,
+        };
+        if variable.is_some() {
+            infix_expression.drain(..2);
+        }
         Calculator::validate_expression(&infix_expression)?;
         let postfix_expression = Calculator::marshal_infix_expression_to_postfix(infix_expression)?;
-        let result = Calculator::evaluate_postfix_expression(postfix_expression)?;
-        Ok(result)
+        let result = Calculator::evaluate_postfix_expression(postfix_expression, context)?;
+        // Integers that no modular operation produced, such as literals and
+        // the results of functions, are reduced here in modular mode, where
+        // any other number has no residue. Otherwise, the same integers are
+        // subject to the overflow policy here.
+        let result = match (result, &context.options.modulus) {
+            (Value::Number(Number::Integer(integer)), None) => {
+                Value::Number(Calculator::apply_overflow_policy(
+                    Operator::Identity,
+                    vec![integer.clone()],
+                    integer,
+                    &context.options,
+                )?)
+            }
+            (Value::Number(Number::Integer(integer)), Some(modulus)) => {
+                Value::Number(Number::Integer(integer.rem_euclid(modulus)))
+            }
+            (result @ (Value::Number(_) | Value::Complex(_)), Some(modulus)) => {
+                return Err(Error::NoResidue(Box::new(result), modulus.clone()))
+            }
+            (result, _) => result,
+        };
+        if let Some(name) = variable {
+            context.assign(name, result.clone());
+        }
+        Ok(Some(result))
+    }
+
+    /// Inserts a multiplication between each pair of adjacent tokens that would
+    /// otherwise be missing an operator, such as an operand followed by an
+    /// opening parenthesis or a closing parenthesis followed by an operand.
+    fn insert_implicit_multiplication(
+        expression: Vec<Token>,
+        mode: ImplicitMultiplication,
+    ) -> Vec<Token> {
+        let operator = match mode {
+            ImplicitMultiplication::Tight => Operator::ImplicitMultiply,
+            _ => Operator::Multiply,
+        };
+        let mut output: Vec<Token> = Vec::with_capacity(expression.len());
+        for token in expression {
+            let ends_operand = output.last().is_some_and(Token::ends_operand);
+            let starts_operand =
+                token.is_value() || matches!(token, Token::LeftParen | Token::Function(_, _));
+            if ends_operand && starts_operand {
+                output.push(Token::Operator(operator));
+            }
+            output.push(token);
+        }
+        output
+    }
+
+    /// Inserts a multiplication between a number and the name of a unit that
+    /// directly follows it, such as in `5 km`, so that the unit applies to the
+    /// number on its own. The multiplication binds more tightly than `*` and
+    /// `/`, so `9.81 m/s^2` divides by seconds. A unit divided by another with
+    /// no spaces around the `/`, such as `MB/s`, is grouped into a single
+    /// unit, so `1 TiB / 100 MB/s` divides by the rate. Names of variables and
+    /// constants are left alone, so they can shadow units.
+    fn attach_units(expression: Vec<Token>, context: &Context) -> Vec<Token> {
+        let is_unit = |token: &Token| {
+            matches!(token, Token::Identifier(name, _)
+                if context.resolve(name).is_none()
+                    && constants::lookup(name, &context.options).is_none()
+                    && units::lookup(name, &context.options).is_some())
+        };
+        let mut output: Vec<Token> = Vec::with_capacity(expression.len());
+        let mut index = 0;
+        while index < expression.len() {
+            let token = &expression[index];
+            let follows_number = matches!(output.last(), Some(Token::Operand(_)));
+            if !(follows_number && is_unit(token)) {
+                output.push(token.clone());
+                index += 1;
+                continue;
+            }
+            output.push(Token::Operator(Operator::ImplicitMultiply));
+            let length = match (token, &expression[index + 1..]) {
+                (
+                    Token::Identifier(name, position),
+                    [Token::Operator(Operator::Divide), per @ Token::Identifier(_, next), rest @ ..],
+                ) if is_unit(per) && *next == position + name.chars().count() + 1 => match rest {
+                    [Token::Operator(Operator::Power), Token::Operand(_), ..] => 5,
+                    _ => 3,
+                },
+                _ => 1,
+            };
+            if length == 1 {
+                output.push(token.clone());
+            } else {
+                output.push(Token::LeftParen);
+                output.extend_from_slice(&expression[index..index + length]);
+                output.push(Token::RightParen);
+            }
+            index += length;
+        }
+        output
+    }
+
+    /// Parses a statement of the form `name(parameters) = expression` into a
+    /// function. Returns `None` if the statement is not a function definition.
+    fn parse_function_definition(statement: &[Token]) -> Option<Result<(String, UserFunction)>> {
+        let (name, position) = match statement {
+            [Token::Function(name, position), ..] => (name, *position),
+            _ => return None,
+        };
+        let assign = statement.iter().position(|token| *token == Token::Assign)?;
+        let invalid = || {
+            Err(Error::InvalidExpression(format!(
+                "definition of function {} must be of the form {}(parameters) = expression",
+                name, name
+            )))
+        };
+        if functions::lookup(name).is_some() {
+            return Some(Err(Error::InvalidExpression(format!(
+                "function {} at position {} is built in and cannot be redefined",
+                name, position
+            ))));
+        }
+        let parameter_list = match &statement[1..assign] {
+            [Token::LeftParen, parameter_list @ .., Token::RightParen] => parameter_list,
+            _ => return Some(invalid()),
+        };
+        let mut parameters: Vec<String> = vec![];
+        for (i, token) in parameter_list.iter().enumerate() {
+            match token {
+                Token::Identifier(parameter, _) if i % 2 == 0 => {
+                    if parameters.contains(parameter) {
+                        return Some(Err(Error::InvalidExpression(format!(
+                            "function {} has more than one parameter named {}",
+                            name, parameter
+                        ))));
+                    }
+                    parameters.push(parameter.clone())
+                }
+                Token::Comma if i % 2 == 1 && i + 1 < parameter_list.len() => (),
+                _ => return Some(invalid()),
+            }
+        }
+        let body = statement[assign + 1..].to_vec();
+        if let Err(err) = Calculator::validate_expression(&body) {
+            return Some(Err(err));
+        }
+        Some(Ok((name.clone(), UserFunction { parameters, body })))
     }
 
     /// Takes an input string and tokenizes it into a mathematical expression
@@ -31,24 +308,208 @@ impl Calculator {
         let mut chars = input.chars().enumerate().peekable();
         while let Some((i, char)) = chars.next() {
             let base_10 = 10;
-            if char.is_digit(base_10) {
+            let radix = match (char, chars.peek()) {
+                ('0', Some((_, 'x'))) => Some(16),
+                ('0', Some((_, 'o'))) => Some(8),
+                ('0', Some((_, 'b'))) => Some(2),
+                _ => None,
+            };
+            if let Some(radix) = radix {
+                let (prefix_position, prefix) = chars.next().unwrap(); // Guaranteed to exist because of match condition
+                let mut digits = String::new();
+                while let Some((j, char)) = chars.peek() {
+                    if char.is_digit(radix) {
+                        digits.push(*char);
+                    } else if char.is_alphanumeric() {
+                        return Err(Error::InvalidCharacter(*char, *j));
+                    } else if *char != '_' {
+                        break;
+                    }
+                    chars.next();
+                }
+                match BigInt::from_str_radix(&digits, radix) {
+                    Some(integer) => output.push(Token::Operand(Number::Integer(integer))),
+                    None => return Err(Error::InvalidCharacter(prefix, prefix_position)),
+                }
+            } else if char.is_digit(base_10) || char == '.' {
                 let mut num = String::from(char);
-                while let Some((_, char)) = chars.peek() {
+                while let Some((j, char)) = chars.peek() {
                     if char.is_digit(base_10) {
                         num.push(chars.next().unwrap().1); // Guaranteed to exist because of while let condition
+                    } else if *char == '.' {
+                        if num.contains('.') {
+                            return Err(Error::InvalidCharacter('.', *j));
+                        }
+                        num.push(chars.next().unwrap().1); // Guaranteed to exist because of while let condition
+                    } else {
+                        break;
+                    }
+                }
+                let mut lookahead = chars.clone().map(|(_, next)| next);
+                let has_exponent = lookahead.next() == Some('e')
+                    && match lookahead.next() {
+                        Some('+' | '-') => {
+                            lookahead.next().is_some_and(|next| next.is_ascii_digit())
+                        }
+                        next => next.is_some_and(|next| next.is_ascii_digit()),
+                    };
+                if has_exponent && num != "." {
+                    num.push(chars.next().unwrap().1); // Guaranteed to exist because of the lookahead
+                    while let Some((_, char)) = chars.peek() {
+                        if char.is_ascii_digit()
+                            || (num.ends_with('e') && matches!(char, '+' | '-'))
+                        {
+                            num.push(chars.next().unwrap().1); // Guaranteed to exist because of while let condition
+                        } else {
+                            break;
+                        }
+                    }
+                }
+                let operand = if num == "." {
+                    return Err(Error::InvalidCharacter('.', i));
+                } else if num.contains('e') {
+                    let float: f64 = num.parse().unwrap(); // Guaranteed to succeed because of the lookahead
+                    if float.is_infinite() {
+                        let message = format!("{} is too large for a float", num);
+                        return Err(Error::InvalidExpression(message));
+                    }
+                    Number::Float(float)
+                } else if num.contains('.') {
+                    Number::Rational(Rational::from_decimal_str(&num).unwrap()) // Guaranteed to succeed because of the checks above
+                } else {
+                    Number::Integer(num.parse().unwrap()) // Guaranteed to succeed because of if condition
+                };
+                if let Number::Integer(_) = operand {
+                    let rest: Vec<char> = chars.clone().map(|(_, next)| next).take(32).collect();
+                    if let Some((token, length)) = Calculator::date_or_duration_literal(
+                        &num,
+                        &rest,
+                        i,
+                        Calculator::in_open_conditional(&output),
+                    )? {
+                        chars.nth(length - 1); // Guaranteed to exist because every literal takes up at least one more character
+                        output.push(token);
+                        continue;
+                    }
+                }
+                // A number directly followed by an i that does not start a
+                // longer word is an imaginary literal, such as 2i or 0.5i.
+                let mut rest = chars.clone().map(|(_, next)| next);
+                if rest.next() == Some('i')
+                    && !rest
+                        .next()
+                        .is_some_and(|next| next.is_alphanumeric() || next == '_')
+                {
+                    chars.next();
+                    output.push(Token::Imaginary(operand));
+                } else {
+                    output.push(Token::Operand(operand));
+                }
+            } else if char.is_alphabetic() {
+                let mut word = String::from(char);
+                while let Some((_, char)) = chars.peek() {
+                    if char.is_alphanumeric() || *char == '_' {
+                        word.push(chars.next().unwrap().1); // Guaranteed to exist because of while let condition
                     } else {
                         break;
                     }
                 }
-                output.push(Token::Operand(num.parse().unwrap())); // Guaranteed to succeed because of if condition
+                // Any word other than an operator keyword names a function when it
+                // is followed by the parenthesis that opens its arguments, or a
+                // value like a constant otherwise.
+                let opens_call = chars.clone().find(|(_, next)| *next != ' ');
+                match Operator::from_keyword(&word) {
+                    Some(operator) => output.push(Token::Operator(operator)),
+                    None if word == "true" || word == "false" => {
+                        output.push(Token::Boolean(word == "true"))
+                    }
+                    None if opens_call.is_some_and(|(_, next)| next == '(') => {
+                        output.push(Token::Function(word, i))
+                    }
+                    None => output.push(Token::Identifier(word, i)),
+                }
             } else {
+                // A sign is unary when there is no operand before it for a binary
+                // operator to apply to, like at the start of the expression or
+                // after an opening parenthesis or a comma. Postfix operators
+                // complete the operand before them, so a sign after them is binary.
+                let expects_operand = match output.last() {
+                    Some(Token::Operator(operator)) => !operator.is_postfix(),
+                    last => matches!(
+                        last,
+                        None | Some(Token::LeftParen)
+                            | Some(Token::Comma)
+                            | Some(Token::Assign)
+                            | Some(Token::Semicolon)
+                    ),
+                };
                 match char {
+                    '+' if expects_operand => output.push(Token::Operator(Operator::Identity)),
+                    '-' if expects_operand => output.push(Token::Operator(Operator::Negate)),
                     '+' => output.push(Token::Operator(Operator::Add)),
                     '-' => output.push(Token::Operator(Operator::Subtract)),
+                    '*' if chars.peek().is_some_and(|(_, next)| *next == '*') => {
+                        chars.next();
+                        output.push(Token::Operator(Operator::Power))
+                    }
                     '*' => output.push(Token::Operator(Operator::Multiply)),
+                    '/' if chars.peek().is_some_and(|(_, next)| *next == '/') => {
+                        chars.next();
+                        output.push(Token::Operator(Operator::FloorDivide))
+                    }
                     '/' => output.push(Token::Operator(Operator::Divide)),
+                    '%' if Calculator::is_percent_sign(chars.clone().map(|(_, next)| next)) => {
+                        output.push(Token::Operator(Operator::Percent))
+                    }
+                    '%' => output.push(Token::Operator(Operator::Remainder)),
+                    '!' if chars.peek().is_some_and(|(_, next)| *next == '=') => {
+                        chars.next();
+                        output.push(Token::Operator(Operator::NotEqual))
+                    }
+                    '!' if chars.peek().is_some_and(|(_, next)| *next == '!') => {
+                        chars.next();
+                        output.push(Token::Operator(Operator::DoubleFactorial))
+                    }
+                    '!' => output.push(Token::Operator(Operator::Factorial)),
+                    '^' => output.push(Token::Operator(Operator::Power)),
+                    '&' => output.push(Token::Operator(Operator::BitwiseAnd)),
+                    '|' => output.push(Token::Operator(Operator::BitwiseOr)),
+                    '~' => output.push(Token::Operator(Operator::BitwiseNot)),
+                    '<' if chars.peek().is_some_and(|(_, next)| *next == '<') => {
+                        chars.next();
+                        output.push(Token::Operator(Operator::ShiftLeft))
+                    }
+                    '<' if chars.peek().is_some_and(|(_, next)| *next == '=') => {
+                        chars.next();
+                        output.push(Token::Operator(Operator::LessEqual))
+                    }
+                    '<' => output.push(Token::Operator(Operator::Less)),
+                    '>' if chars.peek().is_some_and(|(_, next)| *next == '>') => {
+                        chars.next();
+                        if chars.peek().is_some_and(|(_, next)| *next == '>') {
+                            chars.next();
+                            output.push(Token::Operator(Operator::LogicalShiftRight))
+                        } else {
+                            output.push(Token::Operator(Operator::ShiftRight))
+                        }
+                    }
+                    '>' if chars.peek().is_some_and(|(_, next)| *next == '=') => {
+                        chars.next();
+                        output.push(Token::Operator(Operator::GreaterEqual))
+                    }
+                    '>' => output.push(Token::Operator(Operator::Greater)),
+                    '?' => output.push(Token::Operator(Operator::Conditional)),
+                    ':' => output.push(Token::Operator(Operator::ConditionalElse)),
+                    '∠' => output.push(Token::Operator(Operator::Polar)),
                     '(' => output.push(Token::LeftParen),
                     ')' => output.push(Token::RightParen),
+                    ',' => output.push(Token::Comma),
+                    '=' if chars.peek().is_some_and(|(_, next)| *next == '=') => {
+                        chars.next();
+                        output.push(Token::Operator(Operator::Equal))
+                    }
+                    '=' => output.push(Token::Assign),
+                    ';' => output.push(Token::Semicolon),
                     ' ' => continue,
                     _ => return Err(Error::InvalidCharacter(char, i)),
                 }
@@ -57,238 +518,2891 @@ impl Calculator {
         Ok(output)
     }
 
-    /// This function conducts basic validations on the input expression,
-    /// like making sure that operators have operands on both sides and
-    /// making sure that there are no consecutive operands. It also checks
-    /// for zero-length expression. It does not handle parentheses matching validation.  
-    fn validate_expression(expression: &[Token]) -> Result<()> {
-        if expression.is_empty() {
-            return Err(Error::ZeroLengthExpression);
-        }
-        let mut tokens = expression.iter().peekable();
-        let mut previous: Option<&Token> = None;
-        while let Some(token) = tokens.next() {
-            let next = tokens.peek();
-            match token {
-                Token::Operand(operand) => {
-                    if let Some(Token::Operand(next_operand)) = next {
-                        return Err(Error::InvalidExpression(format!(
-                            "consecutive operands {:?}, {:?}",
-                            operand, next_operand
-                        )));
-                    }
-                }
-                Token::Operator(operator) => {
-                    if previous.is_none() {
-                        return Err(Error::InvalidExpression(format!(
-                            "operator {:?} with no leading operand",
-                            operator
-                        )));
-                    }
-                    if next.is_none() {
-                        return Err(Error::InvalidExpression(format!(
-                            "operator {:?} with no trailing operand",
-                            operator
-                        )));
-                    }
-                    match next.unwrap() {
-                        Token::Operand(_) => (),
-                        Token::LeftParen => (),
-                        next_token => {
-                            return Err(Error::InvalidExpression(format!(
-                                "operator {:?} followed by invalid token {:?}",
-                                operator, next_token
-                            )));
-                        }
-                    }
-                }
-                _ => (),
-            }
-            previous = Some(token);
+    /// Decides whether a `%` is a postfix percent rather than the remainder
+    /// operator, given the characters that follow it. It is a percent when
+    /// nothing that could be the right-hand operand of a remainder follows it,
+    /// like in `50%`, `(50%)`, or `50% * 2`. A sign only starts an operand when
+    /// it is attached to one, so `7 % -3` is a remainder while `50% - 3`
+    /// subtracts from a percentage. A `~` always starts an operand, since it is
+    /// never a binary operator, so `7 % ~3` and `7 % ~ 3` are both remainders.
+    fn is_percent_sign(rest: impl Iterator<Item = char>) -> bool {
+        let mut rest = rest.skip_while(|next| *next == ' ').peekable();
+        match rest.next() {
+            None => true,
+            Some('+') | Some('-') => rest.next().is_none_or(|next| next == ' '),
+            Some(next) if next.is_alphabetic() => {
+                let word: String = std::iter::once(next)
+                    .chain(rest.take_while(|next| next.is_alphanumeric() || *next == '_'))
+                    .collect();
+                Operator::from_keyword(&word).is_some()
+            }
+            Some(next) => !(next.is_ascii_digit() || next == '.' || next == '(' || next == '~'),
+        }
+    }
+
+    /// Recognizes a date, time, or duration literal that starts with the
+    /// digits of a whole number, given the characters that follow them, and
+    /// returns its token along with how many of those characters it takes up.
+    /// A date is written as `2026-10-18`, optionally followed by a time of
+    /// day as in `2026-10-18T14:30`. A time of day on its own, such as
+    /// `14:30`, is the time since midnight, except inside a conditional
+    /// expression whose `:` has not been reached yet. A duration is made up of
+    /// two or more numbers with the suffixes `w`, `d`, `h`, `m`, and `s` from
+    /// largest to smallest, such as `3h 20m`, since a single one, such as
+    /// `20m`, is a number of a unit instead.
+    fn date_or_duration_literal(
+        digits: &str,
+        rest: &[char],
+        position: usize,
+        in_conditional: bool,
+    ) -> Result<Option<(Token, usize)>> {
+        let number = |from: usize, length: usize| -> Option<i64> {
+            let digits = rest.get(from..from + length)?;
+            let digits: String = digits.iter().take_while(|c| c.is_ascii_digit()).collect();
+            (digits.len() == length).then(|| digits.parse().ok())?
+        };
+        let ends_at = |at: usize| {
+            !rest
+                .get(at)
+                .is_some_and(|next| next.is_alphanumeric() || *next == '_')
+        };
+        // The minutes and optional seconds of a time of day, written as :MM
+        // or :MM:SS after the hours, along with how many characters they take up
+        let clock = |at: usize| -> Option<(i64, i64, usize)> {
+            let minute = (rest.get(at) == Some(&':'))
+                .then(|| number(at + 1, 2))
+                .flatten()?;
+            match (rest.get(at + 3), number(at + 4, 2)) {
+                (Some(':'), Some(second)) => Some((minute, second, 6)),
+                _ => Some((minute, 0, 3)),
+            }
+        };
+        let time_of_day = |hour: i64, minute: i64, second: i64| {
+            (hour < 24 && minute < 60 && second < 60).then_some(hour * 3600 + minute * 60 + second)
+        };
+        let invalid = |length: usize| {
+            Error::InvalidExpression(format!(
+                "invalid date or time {}{} at position {}",
+                digits,
+                rest[..length].iter().collect::<String>(),
+                position
+            ))
+        };
+        if digits.len() == 4 && rest.first() == Some(&'-') && rest.get(3) == Some(&'-') {
+            if let (Some(month), Some(day)) = (number(1, 2), number(4, 2)) {
+                let (time, length) = match (rest.get(6), number(7, 2)) {
+                    (Some('T'), Some(hour)) => match clock(9) {
+                        Some((minute, second, length)) => {
+                            (Some(time_of_day(hour, minute, second)), 9 + length)
+                        }
+                        None => return Err(invalid(9)),
+                    },
+                    _ => (None, 6),
+                };
+                if !ends_at(length) {
+                    return Err(invalid(length));
+                }
+                let year = digits.parse().unwrap(); // Guaranteed to succeed because of if condition
+                let date = match time {
+                    Some(second) => second.and_then(|second| {
+                        DateTime::new(year, month as u32, day as u32, second, true)
+                    }),
+                    None => DateTime::new(year, month as u32, day as u32, 0, false),
+                };
+                return match date {
+                    Some(date) => Ok(Some((Token::Date(date), length))),
+                    None => Err(invalid(length)),
+                };
+            }
+        }
+        if digits.len() <= 2 && !in_conditional {
+            if let Some((minute, second, length)) = clock(0).filter(|clock| ends_at(clock.2)) {
+                let hour = digits.parse().unwrap(); // Guaranteed to succeed because of if condition
+                let seconds = time_of_day(hour, minute, second).ok_or_else(|| invalid(length))?;
+                let duration = match length {
+                    3 => units::quantity(Rational::new(seconds.into(), 60.into()), "min"),
+                    _ => units::quantity(Rational::from(BigInt::from(seconds)), "s"),
+                };
+                return Ok(Some((Token::Duration(duration), length)));
+            }
+        }
+        // The suffixes of durations in descending order, each with the number
+        // of seconds it stands for and the unit of a duration that ends with it
+        const SUFFIXES: [(char, i64, &str); 5] = [
+            ('w', 604_800, "weeks"),
+            ('d', 86_400, "days"),
+            ('h', 3600, "h"),
+            ('m', 60, "min"),
+            ('s', 1, "s"),
+        ];
+        let suffix = |at: usize| {
+            let index = SUFFIXES
+                .iter()
+                .position(|(suffix, _, _)| rest.get(at) == Some(suffix))?;
+            let attached = rest
+                .get(at + 1)
+                .is_some_and(|next| next.is_alphabetic() || *next == '_');
+            (!attached).then_some(index)
+        };
+        let Some(mut last) = suffix(0) else {
+            return Ok(None);
+        };
+        let part = |digits: &str, suffix: usize| {
+            &digits.parse::<BigInt>().unwrap() * &BigInt::from(SUFFIXES[suffix].1)
+            // Guaranteed to succeed because only digits are passed
+        };
+        let mut seconds = part(digits, last);
+        let (mut length, mut parts) = (1, 1);
+        loop {
+            let start = length + rest[length..].iter().take_while(|c| **c == ' ').count();
+            let count = rest[start..]
+                .iter()
+                .take_while(|c| c.is_ascii_digit())
+                .count();
+            match suffix(start + count) {
+                Some(next) if count > 0 && next > last => {
+                    let digits: String = rest[start..start + count].iter().collect();
+                    seconds = &seconds + &part(&digits, next);
+                    (last, length, parts) = (next, start + count + 1, parts + 1);
+                }
+                _ => break,
+            }
+        }
+        if parts < 2 {
+            return Ok(None);
+        }
+        let (_, factor, unit) = SUFFIXES[last];
+        let duration = units::quantity(Rational::new(seconds, BigInt::from(factor)), unit);
+        Ok(Some((Token::Duration(duration), length)))
+    }
+
+    /// Decides whether the tokens so far end inside a conditional expression
+    /// whose `:` has not been reached yet, at the current depth of
+    /// parentheses. That `:` belongs to the conditional expression, so
+    /// `true ? 1:20` is not a time of day, while `true ? (1:20) : 0` is.
+    fn in_open_conditional(output: &[Token]) -> bool {
+        let statement = output
+            .iter()
+            .rposition(|token| *token == Token::Semicolon)
+            .map_or(output, |semicolon| &output[semicolon + 1..]);
+        let mut open: Vec<usize> = vec![0];
+        for token in statement {
+            match token {
+                Token::LeftParen => open.push(0),
+                Token::RightParen if open.len() > 1 => {
+                    open.pop();
+                }
+                Token::Operator(Operator::Conditional) => *open.last_mut().unwrap() += 1, // Guaranteed to exist because the outermost level is never popped
+                Token::Operator(Operator::ConditionalElse) => {
+                    let last = open.last_mut().unwrap(); // Guaranteed to exist because the outermost level is never popped
+                    *last = last.saturating_sub(1);
+                }
+                _ => (),
+            }
+        }
+        open.last().is_some_and(|open| *open > 0)
+    }
+
+    /// This function conducts basic validations on the input expression,
+    /// like making sure that binary operators have operands on both sides,
+    /// that unary operators have an operand after them, and
+    /// making sure that there are no consecutive operands. It also checks
+    /// that commas separate arguments, that parentheses are not empty unless
+    /// they hold the arguments of a function call, and that operands and
+    /// parentheses are not next to each other without an operator, as in
+    /// `2(3)` or `(1)(2)`. It also checks
+    /// for zero-length expression. It does not handle parentheses matching validation.  
+    fn validate_expression(expression: &[Token]) -> Result<()> {
+        if expression.is_empty() {
+            return Err(Error::ZeroLengthExpression);
+        }
+        let mut tokens = expression.iter().peekable();
+        let mut previous: Option<&Token> = None;
+        while let Some(token) = tokens.next() {
+            let next = tokens.peek();
+            match token {
+                Token::Operand(_)
+                | Token::Boolean(_)
+                | Token::Imaginary(_)
+                | Token::Date(_)
+                | Token::Duration(_)
+                | Token::Identifier(_, _) => match next {
+                    Some(next_token) if next_token.is_value() => {
+                        return Err(Error::InvalidExpression(format!(
+                            "consecutive operands {:?}, {:?}",
+                            token, next_token
+                        )));
+                    }
+                    Some(Token::Function(name, _)) => {
+                        return Err(Error::InvalidExpression(format!(
+                            "operand {:?} followed by function {}",
+                            token, name
+                        )));
+                    }
+                    Some(Token::LeftParen) => {
+                        return Err(Error::InvalidExpression(format!(
+                            "operand {:?} followed by opening parenthesis",
+                            token
+                        )));
+                    }
+                    _ => (),
+                },
+                Token::Operator(operator) => {
+                    let follows_operand = previous.is_some_and(Token::ends_operand);
+                    if operator.is_unary() && follows_operand {
+                        return Err(Error::InvalidExpression(format!(
+                            "unary operator {:?} follows an operand",
+                            operator
+                        )));
+                    }
+                    if !operator.is_unary() && !follows_operand {
+                        return Err(Error::InvalidExpression(format!(
+                            "operator {:?} with no leading operand",
+                            operator
+                        )));
+                    }
+                    if operator.is_postfix() {
+                        // A postfix operator completes the operand before it, so
+                        // it must be followed by whatever could follow that operand.
+                        match next {
+                            Some(next_token)
+                                if next_token.is_value()
+                                    || matches!(
+                                        next_token,
+                                        Token::LeftParen | Token::Function(_, _)
+                                    )
+                                    || matches!(next_token, Token::Operator(next_operator) if next_operator.is_unary()) =>
+                            {
+                                return Err(Error::InvalidExpression(format!(
+                                    "postfix operator {:?} followed by invalid token {:?}",
+                                    operator, next_token
+                                )));
+                            }
+                            _ => (),
+                        }
+                        previous = Some(token);
+                        continue;
+                    }
+                    if next.is_none() {
+                        return Err(Error::InvalidExpression(format!(
+                            "operator {:?} with no trailing operand",
+                            operator
+                        )));
+                    }
+                    match next.unwrap() {
+                        next_token if next_token.is_value() => (),
+                        Token::LeftParen => (),
+                        Token::Function(_, _) => (),
+                        Token::Operator(next_operator) if next_operator.is_unary() => (),
+                        next_token => {
+                            return Err(Error::InvalidExpression(format!(
+                                "operator {:?} followed by invalid token {:?}",
+                                operator, next_token
+                            )));
+                        }
+                    }
+                }
+                Token::Comma => {
+                    if !previous.is_some_and(Token::ends_operand) {
+                        return Err(Error::InvalidExpression(
+                            "comma with no leading argument".into(),
+                        ));
+                    }
+                    match next {
+                        Some(next_token) if next_token.is_value() => (),
+                        Some(Token::LeftParen) => (),
+                        Some(Token::Function(_, _)) => (),
+                        Some(Token::Operator(next_operator)) if next_operator.is_unary() => (),
+                        _ => {
+                            return Err(Error::InvalidExpression(
+                                "comma with no trailing argument".into(),
+                            ))
+                        }
+                    }
+                }
+                Token::LeftParen => {
+                    let is_call = matches!(previous, Some(Token::Function(_, _)));
+                    if !is_call && matches!(next, Some(Token::RightParen)) {
+                        return Err(Error::InvalidExpression("empty parentheses".into()));
+                    }
+                }
+                Token::Assign => {
+                    return Err(Error::InvalidExpression(
+                        "assignment must be of the form name = expression".into(),
+                    ));
+                }
+                Token::RightParen => match next {
+                    Some(Token::Function(name, _)) => {
+                        return Err(Error::InvalidExpression(format!(
+                            "closing parenthesis followed by function {}",
+                            name
+                        )));
+                    }
+                    Some(Token::LeftParen) => {
+                        return Err(Error::InvalidExpression(
+                            "closing parenthesis followed by opening parenthesis".into(),
+                        ));
+                    }
+                    Some(next_token) if next_token.is_value() => {
+                        return Err(Error::InvalidExpression(format!(
+                            "closing parenthesis followed by operand {:?}",
+                            next_token
+                        )));
+                    }
+                    _ => (),
+                },
+                _ => (),
+            }
+            previous = Some(token);
+        }
+        Ok(())
+    }
+
+    /// Converts an expression from infix notation (2 + 2) to Reverse Polish Notation (RPN),
+    /// otherwise known as postfix notation (2 2 +), using the shunting-yard algorithm:
+    /// https://en.wikipedia.org/wiki/Shunting-yard_algorithm. Function calls are
+    /// emitted after their arguments, along with the number of arguments found.
+    /// Conditional expressions become jumps, so that only the value that is
+    /// chosen is evaluated: `c ? a : b` becomes `c [skip past a] a [skip past b] b`.
+    /// While a conditional is open, the operator stack holds the position of
+    /// the jump that is waiting for its target.
+    /// This function performs no validation beyond parentheses matching, comma
+    /// placement, and checking function names and argument counts; it assumes
+    /// that the input infix expression is otherwise valid.
+    fn marshal_infix_expression_to_postfix(expression: Vec<Token>) -> Result<Vec<Token>> {
+        let mut output: Vec<Token> = vec![];
+        let mut operator_stack: Vec<Token> = vec![];
+        // Tracks each open parenthesis, along with the number of arguments
+        // seen so far when the parenthesis opens a function call.
+        let mut argument_counts: Vec<Option<usize>> = vec![];
+        let mut previous_was_left_paren = false;
+        for token in expression {
+            let is_left_paren = token == Token::LeftParen;
+            let after_left_paren = std::mem::replace(&mut previous_was_left_paren, is_left_paren);
+            match token {
+                Token::Operand(operand) => output.push(Token::Operand(operand)),
+                Token::Boolean(boolean) => output.push(Token::Boolean(boolean)),
+                Token::Imaginary(operand) => output.push(Token::Imaginary(operand)),
+                Token::Date(date) => output.push(Token::Date(date)),
+                Token::Duration(duration) => output.push(Token::Duration(duration)),
+                Token::Identifier(name, position) => output.push(Token::Identifier(name, position)),
+                Token::Function(name, position) => {
+                    operator_stack.push(Token::Function(name, position))
+                }
+                Token::LeftParen => {
+                    let is_call = matches!(operator_stack.last(), Some(Token::Function(_, _)));
+                    argument_counts.push(is_call.then_some(1));
+                    operator_stack.push(Token::LeftParen)
+                }
+                Token::Comma => {
+                    Calculator::pop_operators(&mut output, &mut operator_stack)?;
+                    match argument_counts.last_mut() {
+                        Some(Some(count)) => *count += 1,
+                        _ => {
+                            return Err(Error::InvalidExpression(
+                                "comma outside of a function call".into(),
+                            ))
+                        }
+                    }
+                }
+                // Postfix operators bind more tightly than any other operator,
+                // so they apply to the operand before them right away.
+                Token::Operator(operator_cur) if operator_cur.is_postfix() => {
+                    output.push(Token::Operator(operator_cur))
+                }
+                // Unary operators apply to the operand that follows them,
+                // so nothing before them can be ready to pop yet.
+                Token::Operator(operator_cur) if operator_cur.is_unary() => {
+                    operator_stack.push(Token::Operator(operator_cur))
+                }
+                Token::Operator(operator_cur) => {
+                    // Stops at an open parenthesis or an open conditional
+                    while let Some(Token::Operator(operator_top)) = operator_stack.last() {
+                        // Pop operators that bind more tightly than the current one, as well
+                        // as those that bind equally tightly if the current one groups from
+                        // the left, so that `8 - 4 - 2` evaluates as `(8 - 4) - 2`.
+                        let (precedence_top, _) = operator_top.properties();
+                        let (precedence_cur, associativity_cur) = operator_cur.properties();
+                        if precedence_top > precedence_cur
+                            || (precedence_top == precedence_cur
+                                && associativity_cur == Associativity::Left)
+                        {
+                            output.push(operator_stack.pop().unwrap()); // Guaranteed to exist because of while let condition
+                        } else {
+                            break;
+                        }
+                    }
+                    match operator_cur {
+                        // The condition is complete, so it is followed by a jump past the
+                        // value for when it is true, which is filled in once that value ends.
+                        Operator::Conditional => {
+                            operator_stack.push(Token::JumpUnless(output.len()));
+                            output.push(Token::JumpUnless(0));
+                        }
+                        Operator::ConditionalElse => {
+                            Calculator::marshal_conditional_else(&mut output, &mut operator_stack)?
+                        }
+                        _ => operator_stack.push(Token::Operator(operator_cur)),
+                    }
+                }
+                Token::RightParen => {
+                    Calculator::pop_operators(&mut output, &mut operator_stack)?;
+                    if operator_stack.pop() != Some(Token::LeftParen) {
+                        return Err(Error::MismatchedParentheses);
+                    }
+                    // Guaranteed to exist because each open parenthesis is tracked
+                    if let Some(count) = argument_counts.pop().unwrap() {
+                        let count = if after_left_paren { 0 } else { count };
+                        let call = Calculator::marshal_function_call(
+                            operator_stack.pop().unwrap(), // Guaranteed to be a function because the count is tracked
+                            count,
+                        )?;
+                        output.push(call);
+                    }
+                }
+                // Calls and jumps only appear in postfix notation
+                Token::Call(_, _, _) | Token::Jump(_) | Token::JumpUnless(_) => unreachable!(),
+                Token::Assign | Token::Semicolon => unreachable!(), // Removed before validation
+            }
+        }
+        Calculator::pop_operators(&mut output, &mut operator_stack)?;
+        if !operator_stack.is_empty() {
+            return Err(Error::MismatchedParentheses);
+        }
+        Ok(output)
+    }
+
+    /// Moves operators from the stack to the output until an open parenthesis,
+    /// which ends an argument or a parenthesized expression, closing any
+    /// conditional expressions along the way. Fails if a conditional is still
+    /// missing the value for when its condition is false.
+    fn pop_operators(output: &mut Vec<Token>, operator_stack: &mut Vec<Token>) -> Result<()> {
+        loop {
+            match operator_stack.last() {
+                Some(Token::Operator(_)) => output.push(operator_stack.pop().unwrap()), // Guaranteed to exist because of match condition
+                Some(Token::Jump(index)) => {
+                    let index = *index;
+                    operator_stack.pop();
+                    output[index] = Token::Jump(output.len() - index - 1);
+                }
+                Some(Token::JumpUnless(_)) => {
+                    return Err(Error::InvalidExpression(
+                        "conditional expression has ? with no matching :".into(),
+                    ))
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    /// Ends the value that a conditional expression has when its condition is
+    /// true. The jump after the condition is filled in to skip to just past it,
+    /// and it is followed by a jump past the value for when the condition is
+    /// false, which is filled in once that value ends.
+    fn marshal_conditional_else(
+        output: &mut Vec<Token>,
+        operator_stack: &mut Vec<Token>,
+    ) -> Result<()> {
+        loop {
+            match operator_stack.pop() {
+                // A nested conditional ends here, as in `a ? b ? c : d : e`
+                Some(Token::Jump(index)) => output[index] = Token::Jump(output.len() - index - 1),
+                Some(Token::JumpUnless(index)) => {
+                    operator_stack.push(Token::Jump(output.len()));
+                    output.push(Token::Jump(0));
+                    output[index] = Token::JumpUnless(output.len() - index - 1);
+                    return Ok(());
+                }
+                _ => {
+                    return Err(Error::InvalidExpression(
+                        "conditional expression has : with no matching ?".into(),
+                    ))
+                }
+            }
+        }
+    }
+
+    /// Turns a function name into a call with the given number of arguments,
+    /// checking that built-in functions accept that many arguments. Other
+    /// names are checked when they are called, since user-defined functions
+    /// can be defined after the expressions that call them.
+    fn marshal_function_call(function: Token, count: usize) -> Result<Token> {
+        let (name, position) = match function {
+            Token::Function(name, position) => (name, position),
+            _ => unreachable!(),
+        };
+        if let Some(builtin) = functions::lookup(&name) {
+            if !builtin.arity.accepts(count) {
+                return Err(Error::ArityMismatch(name, builtin.arity, count, position));
+            }
+        }
+        Ok(Token::Call(name, count, position))
+    }
+
+    /// Calls a user-defined function by evaluating its body with its
+    /// parameters bound to the arguments.
+    fn call_user_function(
+        name: String,
+        arguments: Vec<Value>,
+        position: usize,
+        context: &mut Context,
+    ) -> Result<Value> {
+        let function = match context.function(&name) {
+            Some(function) => function.clone(),
+            None => return Err(Error::UnknownFunction(name, position)),
+        };
+        let arity = Arity::Exactly(function.parameters.len());
+        if !arity.accepts(arguments.len()) {
+            return Err(Error::ArityMismatch(name, arity, arguments.len(), position));
+        }
+        if context.depth() >= MAX_CALL_DEPTH {
+            return Err(Error::RecursionLimit(name, position));
+        }
+        let postfix_expression = Calculator::marshal_infix_expression_to_postfix(function.body)?;
+        context.push_frame(function.parameters.into_iter().zip(arguments).collect());
+        let result = Calculator::evaluate_postfix_expression(postfix_expression, context);
+        context.pop_frame();
+        result
+    }
+
+    /// Evalutes an expression ordered using Reverse Polish Notation (RPN),
+    /// otherwise known as postfix notation. The algorithm proceeds by pushing
+    /// each encountered operand onto a stack, popping the last two off (or the
+    /// last one, for unary operators) and calculating a result for each operator
+    /// encountered, and then pushing the result back on to the stack. Assuming an input with `n` operands
+    /// and `n - 1` operators, in proper order, the output will be the sole
+    /// remaining value on the stack after all tokens have been spent.
+    ///
+    /// Note: This function assumes that the input expression represents a valid
+    /// postfix notation expression. It performs no validation on the input,
+    /// and it will panic if the input is invalid (e.g., '+ 3 2`). It will also
+    /// panic on a zero-length expression. As such, input should be validated before
+    /// being passed to this function.
+    fn evaluate_postfix_expression(expression: Vec<Token>, context: &mut Context) -> Result<Value> {
+        let options = &context.options.clone();
+        let mut operand_stack: Vec<Operand> = vec![];
+        let mut tokens = expression.into_iter();
+        while let Some(token) = tokens.next() {
+            match token {
+                Token::Operand(operand) => operand_stack.push(Operand::Number(
+                    Calculator::apply_numeric_mode(operand, options),
+                )),
+                Token::Boolean(boolean) => operand_stack.push(Operand::Boolean(boolean)),
+                Token::Imaginary(operand) => {
+                    operand_stack.push(Operand::from(Value::from(Complex::new(
+                        Number::from(0),
+                        Calculator::apply_numeric_mode(operand, options),
+                    ))))
+                }
+                Token::Date(date) => operand_stack.push(Operand::Date(date)),
+                Token::Duration(duration) => operand_stack.push(Operand::Quantity(duration)),
+                // Variables take priority over constants and units, so they can shadow them
+                Token::Identifier(name, position) => match context.resolve(&name) {
+                    Some(value) => operand_stack.push(Operand::from(
+                        Calculator::apply_numeric_mode_to_value(value.clone(), options),
+                    )),
+                    None => match constants::lookup(&name, options) {
+                        Some(value) => operand_stack.push(Operand::from(value)),
+                        None => match units::lookup(&name, options) {
+                            Some(unit) => operand_stack.push(Operand::Quantity(unit)),
+                            None => return Err(Error::UnknownVariable(name, position)),
+                        },
+                    },
+                },
+                Token::Operator(operator) if operator.is_unary() || operator.is_postfix() => {
+                    let a = operand_stack.pop().unwrap_or_else(|| {
+                        panic!("Found no operand for operator {:?}.", &operator)
+                    });
+                    let result = match (operator, a) {
+                        (Operator::Negate, Operand::Percent(a)) => {
+                            Operand::Percent(Calculator::negate(a, options)?)
+                        }
+                        (Operator::Negate, Operand::Complex(a)) => {
+                            Operand::Complex(complex::negate(a, options)?)
+                        }
+                        (Operator::Negate, Operand::Quantity(a)) => {
+                            Operand::Quantity(units::negate(a, options)?)
+                        }
+                        (
+                            Operator::Identity,
+                            a @ (Operand::Number(_)
+                            | Operand::Percent(_)
+                            | Operand::Complex(_)
+                            | Operand::Quantity(_)),
+                        ) => a,
+                        (Operator::Not, a) => {
+                            Operand::Boolean(!Calculator::boolean_operand(operator, a)?)
+                        }
+                        (operator, a) => {
+                            let a = a.into_number(operator)?;
+                            match operator {
+                                Operator::Negate => {
+                                    Operand::Number(Calculator::negate(a, options)?)
+                                }
+                                Operator::BitwiseNot => Operand::Number(Number::Integer(
+                                    !Calculator::integer_operand(operator, a)?,
+                                )),
+                                Operator::Factorial | Operator::DoubleFactorial => {
+                                    Operand::Number(Calculator::factorial(operator, a, options)?)
+                                }
+                                Operator::Percent => Operand::Percent(Calculator::percent(a)),
+                                _ => unreachable!(), // Guaranteed to take one operand because of match guard
+                            }
+                        }
+                    };
+                    operand_stack.push(result)
+                }
+                Token::Operator(operator) => {
+                    let b = operand_stack.pop().unwrap_or_else(|| {
+                        panic!("Found no operands for operator {:?}.", &operator)
+                    });
+                    let a = operand_stack.pop().unwrap_or_else(|| {
+                        panic!(
+                            "Found only one operand for operator {:?}: {:?}",
+                            &operator, b
+                        )
+                    });
+                    let result = match (operator, a, b) {
+                        (Operator::Add, Operand::Percent(a), Operand::Percent(b)) => {
+                            Operand::Percent(Calculator::add(a, b, options)?)
+                        }
+                        (Operator::Subtract, Operand::Percent(a), Operand::Percent(b)) => {
+                            Operand::Percent(Calculator::subtract(a, b, options)?)
+                        }
+                        // Adding or subtracting a percentage is relative to the number
+                        // on the left, so 200 + 15% adds 15% of 200.
+                        (Operator::Add, Operand::Number(a), Operand::Percent(b)) => {
+                            let change = Calculator::multiply(a.clone(), b, options)?;
+                            Operand::Number(Calculator::add(a, change, options)?)
+                        }
+                        (Operator::Subtract, Operand::Number(a), Operand::Percent(b)) => {
+                            let change = Calculator::multiply(a.clone(), b, options)?;
+                            Operand::Number(Calculator::subtract(a, change, options)?)
+                        }
+                        (Operator::Polar, a, b) => Operand::from(complex::from_polar(
+                            a.into_number(operator)?,
+                            b.into_number(operator)?,
+                            options,
+                        )?),
+                        (operator @ (Operator::Add | Operator::Subtract), a, b)
+                            if matches!(a, Operand::Date(_)) || matches!(b, Operand::Date(_)) =>
+                        {
+                            Operand::from(date::apply_binary_operator(
+                                operator,
+                                a.into_value(),
+                                b.into_value(),
+                            )?)
+                        }
+                        (operator, a, b)
+                            if units::supports(operator)
+                                && (operator == Operator::Convert
+                                    || matches!(a, Operand::Quantity(_))
+                                    || matches!(b, Operand::Quantity(_))) =>
+                        {
+                            Operand::from(units::apply_binary_operator(
+                                operator,
+                                a.into_value(),
+                                b.into_value(),
+                                options,
+                            )?)
+                        }
+                        (operator, a, b)
+                            if complex::supports(operator)
+                                && (matches!(a, Operand::Complex(_))
+                                    || matches!(b, Operand::Complex(_))
+                                    || matches!((operator, &a, &b), (Operator::Power, Operand::Number(a), Operand::Number(b))
+                                        if complex::is_complex_power(a, b))) =>
+                        {
+                            Operand::from(complex::apply_binary_operator(
+                                operator,
+                                a.into_complex(operator)?,
+                                b.into_complex(operator)?,
+                                options,
+                            )?)
+                        }
+                        (
+                            Operator::Equal
+                            | Operator::NotEqual
+                            | Operator::Less
+                            | Operator::LessEqual
+                            | Operator::Greater
+                            | Operator::GreaterEqual,
+                            a,
+                            b,
+                        ) => Operand::Boolean(Calculator::compare(
+                            operator,
+                            a.into_value(),
+                            b.into_value(),
+                            options,
+                        )?),
+                        (Operator::And, a, b) => Operand::Boolean(
+                            Calculator::boolean_operand(operator, a)?
+                                & Calculator::boolean_operand(operator, b)?,
+                        ),
+                        (Operator::Or, a, b) => Operand::Boolean(
+                            Calculator::boolean_operand(operator, a)?
+                                | Calculator::boolean_operand(operator, b)?,
+                        ),
+                        (operator, a, b) => Operand::Number(Calculator::apply_binary_operator(
+                            operator,
+                            a.into_number(operator)?,
+                            b.into_number(operator)?,
+                            options,
+                        )?),
+                    };
+                    operand_stack.push(result)
+                }
+                Token::Call(name, count, position) => {
+                    let arguments: Vec<Value> = operand_stack
+                        .split_off(operand_stack.len() - count)
+                        .into_iter()
+                        .map(|argument| {
+                            Calculator::apply_numeric_mode_to_value(argument.into_value(), options)
+                        })
+                        .collect();
+                    let result = match functions::lookup(&name) {
+                        Some(builtin) => builtin.call(arguments, options)?,
+                        None => Calculator::call_user_function(name, arguments, position, context)?,
+                    };
+                    operand_stack.push(Operand::from(result))
+                }
+                // Every jump skips at least one token, since each value of a
+                // conditional expression is made up of at least one token.
+                Token::Jump(skip) => {
+                    tokens.nth(skip - 1);
+                }
+                Token::JumpUnless(skip) => {
+                    let condition = operand_stack
+                        .pop()
+                        .expect("Found no condition for conditional expression.");
+                    if !Calculator::boolean_operand(Operator::Conditional, condition)? {
+                        tokens.nth(skip - 1);
+                    }
+                }
+                _ => unreachable!(), // Postfix notation does not use parentheses, commas, function names, or assignments
+            }
+        }
+        let result = operand_stack.pop().expect(
+            "No result value found. Please make sure the input expression has non-zero length.",
+        );
+        // Percentages are exact fractions, which become floats here in float mode
+        Ok(Calculator::apply_numeric_mode_to_value(
+            result.into_value(),
+            options,
+        ))
+    }
+
+    /// Compares two values. Numbers are compared by value, so `1 == 1.0` is
+    /// true, and comparisons with a float that is not a number are all false
+    /// except for `!=`. Complex numbers and booleans can only be tested for
+    /// equality, and booleans only with each other. In modular mode, integers
+    /// are compared by their residues, so `18 == 1` is true modulo 17.
+    fn compare(operator: Operator, a: Value, b: Value, options: &Options) -> Result<bool> {
+        let equality = matches!(operator, Operator::Equal | Operator::NotEqual);
+        let ordering = match (a, b) {
+            (Value::Number(a), Value::Number(b)) => match &options.modulus {
+                Some(modulus) => {
+                    modular::reduce(a, modulus).partial_cmp(&modular::reduce(b, modulus))
+                }
+                None => a.partial_cmp(&b),
+            },
+            (Value::Complex(a), Value::Complex(b)) if equality => {
+                let equal = a.re.partial_cmp(&b.re) == Some(Ordering::Equal)
+                    && a.im.partial_cmp(&b.im) == Some(Ordering::Equal);
+                equal.then_some(Ordering::Equal)
+            }
+            // A complex number always has an imaginary part, so it never equals a real number
+            (Value::Complex(_), Value::Number(_)) | (Value::Number(_), Value::Complex(_))
+                if equality =>
+            {
+                None
+            }
+            (Value::Boolean(a), Value::Boolean(b)) if equality => Some(a.cmp(&b)),
+            (Value::Date(a), Value::Date(b)) => Some(a.cmp(&b)),
+            (a @ Value::Quantity(_), b) | (a @ Value::Number(_), b @ Value::Quantity(_)) => {
+                units::compare(operator, a, b)?
+            }
+            (Value::Number(_), b) => return Err(Error::InvalidOperand(operator, Box::new(b))),
+            (a, _) => return Err(Error::InvalidOperand(operator, Box::new(a))),
+        };
+        Ok(match operator {
+            Operator::Equal => ordering == Some(Ordering::Equal),
+            Operator::NotEqual => ordering != Some(Ordering::Equal),
+            Operator::Less => ordering == Some(Ordering::Less),
+            Operator::LessEqual => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+            Operator::Greater => ordering == Some(Ordering::Greater),
+            Operator::GreaterEqual => {
+                matches!(ordering, Some(Ordering::Greater | Ordering::Equal))
+            }
+            _ => unreachable!(), // Only called for comparison operators
+        })
+    }
+
+    /// Extracts a boolean from an operand, or fails if the operand is a
+    /// number, since the operator only makes sense for booleans.
+    fn boolean_operand(operator: Operator, operand: Operand) -> Result<bool> {
+        match operand {
+            Operand::Boolean(boolean) => Ok(boolean),
+            operand => Err(Error::InvalidOperand(
+                operator,
+                Box::new(operand.into_value()),
+            )),
+        }
+    }
+
+    /// Applies an operator that takes two operands.
+    fn apply_binary_operator(
+        operator: Operator,
+        a: Number,
+        b: Number,
+        options: &Options,
+    ) -> Result<Number> {
+        // In modular mode, integers are residues, except for the number of
+        // bits of a shift, which is an exponent
+        let (a, b) = match options.modulus.as_ref() {
+            Some(modulus) if modular::supports(operator) => {
+                return modular::apply_binary_operator(operator, a, b, modulus);
+            }
+            Some(modulus) => match operator {
+                Operator::ShiftLeft | Operator::ShiftRight | Operator::LogicalShiftRight => {
+                    (modular::reduce(a, modulus), b)
+                }
+                _ => (modular::reduce(a, modulus), modular::reduce(b, modulus)),
+            },
+            None => (a, b),
+        };
+        match operator {
+            Operator::Add => Calculator::add(a, b, options),
+            Operator::Subtract => Calculator::subtract(a, b, options),
+            Operator::Multiply | Operator::ImplicitMultiply | Operator::Of => {
+                Calculator::multiply(a, b, options)
+            }
+            Operator::Divide => Calculator::divide(a, b, options),
+            Operator::FloorDivide => Calculator::floor_divide(a, b, options),
+            Operator::Remainder => Calculator::remainder(a, b),
+            Operator::Modulo => Calculator::modulo(a, b),
+            Operator::Power => Calculator::power(a, b, options),
+            Operator::BitwiseAnd | Operator::BitwiseOr | Operator::BitwiseXor => {
+                Calculator::bitwise(operator, a, b)
+            }
+            Operator::ShiftLeft | Operator::ShiftRight | Operator::LogicalShiftRight => {
+                Calculator::shift(operator, a, b, options)
+            }
+            Operator::Negate
+            | Operator::Identity
+            | Operator::BitwiseNot
+            | Operator::Factorial
+            | Operator::DoubleFactorial
+            | Operator::Percent
+            | Operator::Not => {
+                unreachable!() // Takes a single operand
+            }
+            Operator::Equal
+            | Operator::NotEqual
+            | Operator::Less
+            | Operator::LessEqual
+            | Operator::Greater
+            | Operator::GreaterEqual
+            | Operator::And
+            | Operator::Or
+            | Operator::Conditional
+            | Operator::ConditionalElse => {
+                unreachable!() // Yields or takes booleans, or becomes jumps
+            }
+            Operator::Polar => unreachable!(), // Yields a complex number
+            Operator::Convert => unreachable!(), // Yields a quantity
+        }
+    }
+
+    /// Turns a number into the fraction it stands for as a percentage, so 15
+    /// becomes 0.15. Exact numbers stay exact, so that adding the percentage to
+    /// an amount does not pick up floating point error along the way.
+    fn percent(a: Number) -> Number {
+        if a.is_exact() {
+            Number::from(&a.to_rational() / &Rational::from(BigInt::from(100)))
+        } else {
+            Number::Float(a.to_f64() / 100.0)
+        }
+    }
+
+    fn add(a: Number, b: Number, options: &Options) -> Result<Number> {
+        match (a, b) {
+            (Number::Integer(a), Number::Integer(b)) => {
+                let result = &a + &b;
+                Calculator::apply_overflow_policy(Operator::Add, vec![a, b], result, options)
+            }
+            (a, b) if a.is_exact() && b.is_exact() => {
+                Ok(Number::from(&a.to_rational() + &b.to_rational()))
+            }
+            (a, b) => {
+                let result = a.to_f64() + b.to_f64();
+                Calculator::float_result(Operator::Add, a, b, result)
+            }
+        }
+    }
+
+    fn subtract(a: Number, b: Number, options: &Options) -> Result<Number> {
+        match (a, b) {
+            (Number::Integer(a), Number::Integer(b)) => {
+                let result = &a - &b;
+                Calculator::apply_overflow_policy(Operator::Subtract, vec![a, b], result, options)
+            }
+            (a, b) if a.is_exact() && b.is_exact() => {
+                Ok(Number::from(&a.to_rational() - &b.to_rational()))
+            }
+            (a, b) => {
+                let result = a.to_f64() - b.to_f64();
+                Calculator::float_result(Operator::Subtract, a, b, result)
+            }
+        }
+    }
+
+    fn multiply(a: Number, b: Number, options: &Options) -> Result<Number> {
+        match (a, b) {
+            (Number::Integer(a), Number::Integer(b)) => {
+                let result = &a * &b;
+                Calculator::apply_overflow_policy(Operator::Multiply, vec![a, b], result, options)
+            }
+            (a, b) if a.is_exact() && b.is_exact() => {
+                Ok(Number::from(&a.to_rational() * &b.to_rational()))
+            }
+            (a, b) => {
+                let result = a.to_f64() * b.to_f64();
+                Calculator::float_result(Operator::Multiply, a, b, result)
+            }
+        }
+    }
+
+    /// Divides two numbers. The quotient of two integers remains an integer
+    /// when it is exact. Otherwise, it becomes a rational or a float depending
+    /// on the numeric mode.
+    fn divide(a: Number, b: Number, options: &Options) -> Result<Number> {
+        if b.is_zero() {
+            return Err(Error::DivideByZero(
+                Operator::Divide,
+                Box::new(a.into()),
+                Box::new(b.into()),
+            ));
+        }
+        match (a, b) {
+            (Number::Integer(a), Number::Integer(b)) => {
+                let (quotient, remainder) = a.div_rem(&b);
+                if remainder.is_zero() {
+                    Calculator::apply_overflow_policy(
+                        Operator::Divide,
+                        vec![a, b],
+                        quotient,
+                        options,
+                    )
+                } else if options.mode == NumericMode::Rational {
+                    Ok(Number::Rational(Rational::new(a, b)))
+                } else {
+                    // Dividing the remainder separately keeps the fraction accurate
+                    // even when the operands themselves are too large for a float.
+                    let result = quotient.to_f64() + remainder.to_f64() / b.to_f64();
+                    Calculator::float_result(
+                        Operator::Divide,
+                        Number::Integer(a),
+                        Number::Integer(b),
+                        result,
+                    )
+                }
+            }
+            (a, b) if a.is_exact() && b.is_exact() => {
+                Ok(Number::from(&a.to_rational() / &b.to_rational()))
+            }
+            (a, b) => {
+                let result = a.to_f64() / b.to_f64();
+                Calculator::float_result(Operator::Divide, a, b, result)
+            }
+        }
+    }
+
+    /// Divides two numbers and rounds the quotient toward negative infinity,
+    /// so `7 // 2` is 3 and `-7 // 2` is -4.
+    fn floor_divide(a: Number, b: Number, options: &Options) -> Result<Number> {
+        if b.is_zero() {
+            return Err(Error::DivideByZero(
+                Operator::FloorDivide,
+                Box::new(a.into()),
+                Box::new(b.into()),
+            ));
+        }
+        match (a, b) {
+            (Number::Integer(a), Number::Integer(b)) => {
+                let result = a.div_floor(&b);
+                Calculator::apply_overflow_policy(
+                    Operator::FloorDivide,
+                    vec![a, b],
+                    result,
+                    options,
+                )
+            }
+            (a, b) if a.is_exact() && b.is_exact() => Ok(Number::Integer(
+                (&a.to_rational() / &b.to_rational()).floor(),
+            )),
+            (a, b) => {
+                let result = (a.to_f64() / b.to_f64()).floor();
+                Calculator::float_result(Operator::FloorDivide, a, b, result)
+            }
+        }
+    }
+
+    /// Returns the remainder of truncated division, which takes the sign of
+    /// the dividend, so `7 % 3` is 1 and `-7 % 3` is -1.
+    fn remainder(a: Number, b: Number) -> Result<Number> {
+        if b.is_zero() {
+            return Err(Error::DivideByZero(
+                Operator::Remainder,
+                Box::new(a.into()),
+                Box::new(b.into()),
+            ));
+        }
+        match (a, b) {
+            (Number::Integer(a), Number::Integer(b)) => Ok(Number::Integer(a.div_rem(&b).1)),
+            (a, b) if a.is_exact() && b.is_exact() => {
+                let (a, b) = (a.to_rational(), b.to_rational());
+                let quotient = Rational::from((&a / &b).trunc());
+                Ok(Number::from(&a - &(&b * &quotient)))
+            }
+            (a, b) => {
+                let result = a.to_f64() % b.to_f64();
+                Calculator::float_result(Operator::Remainder, a, b, result)
+            }
+        }
+    }
+
+    /// Returns the remainder of Euclidean division, which is never negative,
+    /// so `7 mod 3` is 1 and `-7 mod 3` is 2.
+    fn modulo(a: Number, b: Number) -> Result<Number> {
+        if b.is_zero() {
+            return Err(Error::DivideByZero(
+                Operator::Modulo,
+                Box::new(a.into()),
+                Box::new(b.into()),
+            ));
+        }
+        match (a, b) {
+            (Number::Integer(a), Number::Integer(b)) => Ok(Number::Integer(a.rem_euclid(&b))),
+            (a, b) if a.is_exact() && b.is_exact() => {
+                let a = a.to_rational();
+                let b = match b.to_rational() {
+                    b if b.numerator().is_negative() => -b,
+                    b => b,
+                };
+                let quotient = Rational::from((&a / &b).floor());
+                Ok(Number::from(&a - &(&b * &quotient)))
+            }
+            (a, b) => {
+                let result = a.to_f64().rem_euclid(b.to_f64());
+                Calculator::float_result(Operator::Modulo, a, b, result)
+            }
+        }
+    }
+
+    /// Raises a number to a power. Exact bases raised to integer exponents are
+    /// computed exactly, while everything else is approximated with floats.
+    /// A negative exponent produces a rational or a float depending on the
+    /// numeric mode, and fails for a base of zero in either mode. Powers that
+    /// are not real numbers, such as `(-4)^0.5`, fail too.
+    fn power(a: Number, b: Number, options: &Options) -> Result<Number> {
+        if a.is_zero() && b.to_f64() < 0.0 {
+            return Err(Error::DivideByZero(
+                Operator::Power,
+                Box::new(a.into()),
+                Box::new(b.into()),
+            ));
+        }
+        let exponent = match &b {
+            Number::Integer(exponent) if a.is_exact() => exponent.clone(),
+            _ => return Calculator::power_float(a, b),
+        };
+        if exponent.is_negative() {
+            if options.mode == NumericMode::Float {
+                return Calculator::power_float(a, b);
+            }
+            let reciprocal = Calculator::power(a, Number::Integer(-exponent), options)?;
+            return Ok(Number::from(
+                &Rational::from(BigInt::from(1)) / &reciprocal.to_rational(),
+            ));
+        }
+        match a {
+            Number::Integer(base) => Calculator::power_integer(base, exponent, options),
+            Number::Rational(base) => {
+                let parts = [base.numerator(), base.denominator()];
+                let mut powers = vec![];
+                for part in parts {
+                    match Calculator::checked_pow(part, &exponent) {
+                        Some(power) => powers.push(power),
+                        None => {
+                            return Err(Error::Overflow(
+                                Operator::Power,
+                                vec![part.clone(), exponent],
+                                MAX_INTEGER_BITS,
+                            ))
+                        }
+                    }
+                }
+                let denominator = powers.pop().unwrap(); // Guaranteed to exist because both parts were pushed
+                let numerator = powers.pop().unwrap(); // Guaranteed to exist because both parts were pushed
+                Ok(Number::from(Rational::new(numerator, denominator)))
+            }
+            Number::Float(_) => unreachable!(), // Guaranteed to be exact because of match guard
+        }
+    }
+
+    /// Raises a number to a power with floats, failing when the result is not
+    /// a number or is too large for a float.
+    fn power_float(a: Number, b: Number) -> Result<Number> {
+        match a.to_f64().powf(b.to_f64()) {
+            result if result.is_nan() && !a.to_f64().is_nan() && !b.to_f64().is_nan() => {
+                Err(Error::InvalidOperand(Operator::Power, Box::new(a.into())))
+            }
+            result => Calculator::float_result(Operator::Power, a, b, result),
+        }
+    }
+
+    /// Returns the result of floating point arithmetic on two numbers, failing
+    /// when it is infinite or not a number although neither number is, which
+    /// happens when the result, or an exact number that went into it, is too
+    /// large for a float.
+    fn float_result(operator: Operator, a: Number, b: Number, result: f64) -> Result<Number> {
+        let is_finite = |number: &Number| !matches!(number, Number::Float(x) if !x.is_finite());
+        if !result.is_finite() && is_finite(&a) && is_finite(&b) {
+            return Err(Error::FloatOverflow(
+                operator,
+                Box::new(a.into()),
+                Box::new(b.into()),
+            ));
+        }
+        Ok(Number::Float(result))
+    }
+
+    /// Raises an integer to a non-negative integer power. Results that are too
+    /// large to compute fail with an overflow error, unless a bounded overflow
+    /// policy is in place, since those only need the lowest bits and the sign
+    /// of the result to wrap or saturate it.
+    fn power_integer(base: BigInt, exponent: BigInt, options: &Options) -> Result<Number> {
+        if let Some(result) = Calculator::checked_pow(&base, &exponent) {
+            return Calculator::apply_overflow_policy(
+                Operator::Power,
+                vec![base, exponent],
+                result,
+                options,
+            );
+        }
+        if options.overflow == OverflowPolicy::Unbounded {
+            return Err(Error::Overflow(
+                Operator::Power,
+                vec![base, exponent],
+                MAX_INTEGER_BITS,
+            ));
+        }
+        let modulus = &BigInt::from(1) << BOUNDED_INTEGER_BITS;
+        let lowest_bits = base.pow_mod(&exponent, &modulus);
+        let result = if base.is_negative() && exponent.is_odd() {
+            &lowest_bits - &(&modulus << 1)
+        } else {
+            &lowest_bits + &modulus
+        };
+        Calculator::apply_overflow_policy(Operator::Power, vec![base, exponent], result, options)
+    }
+
+    /// Raises an integer to a non-negative integer power, or returns `None` if
+    /// the result would be larger than `MAX_INTEGER_BITS` bits.
+    fn checked_pow(base: &BigInt, exponent: &BigInt) -> Option<BigInt> {
+        if base.bits() <= 1 {
+            // Powers of 0, 1, and -1 never grow, however large the exponent.
+            return Some(
+                match (base.is_zero(), exponent.is_zero(), exponent.is_odd()) {
+                    (_, true, _) => BigInt::from(1),
+                    (true, false, _) => BigInt::from(0),
+                    (false, false, true) => base.clone(),
+                    (false, false, false) => base.abs(),
+                },
+            );
+        }
+        let exponent = exponent.to_u64()?;
+        let minimum_bits = (base.bits() as u64 - 1).saturating_mul(exponent);
+        if minimum_bits >= MAX_INTEGER_BITS as u64 {
+            return None;
+        }
+        Some(base.pow(exponent as u32))
+    }
+
+    /// Computes the factorial of a non-negative integer, `n! = n * (n - 1) * ... * 1`,
+    /// or its double factorial, `n!! = n * (n - 2) * ...`, which skips every other factor.
+    fn factorial(operator: Operator, operand: Number, options: &Options) -> Result<Number> {
+        let n = Calculator::integer_operand(operator, operand)?;
+        let count = match n.to_u64() {
+            Some(count) => count,
+            None if n.is_negative() => {
+                return Err(Error::InvalidOperand(
+                    operator,
+                    Box::new(Number::Integer(n).into()),
+                ))
+            }
+            None => return Err(Error::Overflow(operator, vec![n], MAX_INTEGER_BITS)),
+        };
+        let (first, step) = match operator {
+            Operator::DoubleFactorial => (2 - count % 2, 2),
+            _ => (2, 1),
+        };
+        // Stirling's approximation of the size of the result rules out large
+        // operands before spending time on a product that would be discarded.
+        // A double factorial is close to the square root of the factorial.
+        let estimated_bits = {
+            let count = count.max(1) as f64;
+            let bits = (count * count.ln() - count + 0.5 * (2.0 * PI * count).ln()) / LN_2;
+            if step == 2 {
+                bits / 2.0
+            } else {
+                bits
+            }
+        };
+        if options.overflow == OverflowPolicy::Unbounded
+            && estimated_bits > (MAX_INTEGER_BITS + BOUNDED_INTEGER_BITS) as f64
+        {
+            return Err(Error::Overflow(operator, vec![n], MAX_INTEGER_BITS));
+        }
+        // A product of odd factors never wraps around to zero, so wrapping it
+        // takes every factor, which is too many past this point.
+        if options.overflow == OverflowPolicy::Wrapping
+            && first == 1
+            && count > MAX_INTEGER_BITS as u64
+        {
+            return Err(Error::Overflow(operator, vec![n], MAX_INTEGER_BITS));
+        }
+        let modulus = &BigInt::from(1) << BOUNDED_INTEGER_BITS;
+        // Runs of factors are multiplied together while they fit in a machine
+        // integer, so that the large result is multiplied far less often.
+        let mut factors = (first..=count).step_by(step).peekable();
+        let chunks = std::iter::from_fn(|| {
+            let mut chunk = factors.next()?;
+            while let Some(product) = factors
+                .peek()
+                .and_then(|factor| chunk.checked_mul(*factor))
+                .filter(|product| *product <= i64::MAX as u64)
+            {
+                chunk = product;
+                factors.next();
+            }
+            Some(chunk)
+        });
+        let mut result = BigInt::from(1);
+        for chunk in chunks {
+            result = &result * &BigInt::from(chunk as i64);
+            match options.overflow {
+                OverflowPolicy::Unbounded if result.bits() > MAX_INTEGER_BITS => {
+                    return Err(Error::Overflow(operator, vec![n], MAX_INTEGER_BITS));
+                }
+                // Keeping only the lowest bits is enough to wrap the result, and
+                // once they are all zero, further factors cannot change them.
+                OverflowPolicy::Wrapping => {
+                    result = result.rem_euclid(&modulus);
+                    if result.is_zero() {
+                        break;
+                    }
+                }
+                // The result only grows, so once it is out of range, it stays there.
+                OverflowPolicy::Checked | OverflowPolicy::Saturating
+                    if result.bits() > BOUNDED_INTEGER_BITS =>
+                {
+                    break;
+                }
+                _ => (),
+            }
+        }
+        Calculator::apply_overflow_policy(operator, vec![n], result, options)
+    }
+
+    /// Applies a bitwise operator to two integers, treating negative integers
+    /// as if they were stored in two's complement with infinite sign extension.
+    fn bitwise(operator: Operator, a: Number, b: Number) -> Result<Number> {
+        let a = Calculator::integer_operand(operator, a)?;
+        let b = Calculator::integer_operand(operator, b)?;
+        let result = match operator {
+            Operator::BitwiseAnd => &a & &b,
+            Operator::BitwiseOr => &a | &b,
+            Operator::BitwiseXor => &a ^ &b,
+            _ => unreachable!(), // Only called for bitwise operators
+        };
+        Ok(Number::Integer(result))
+    }
+
+    /// Shifts an integer by a non-negative number of bits. Shifting left is
+    /// subject to the overflow policy. An arithmetic right shift preserves the
+    /// sign, while a logical right shift treats negative integers as unsigned
+    /// integers of `BOUNDED_INTEGER_BITS` bits, so `-1 >>> 60` is 15, and
+    /// fails for negative integers that do not fit in that many bits.
+    /// Non-negative integers of any size are shifted as they are.
+    fn shift(operator: Operator, a: Number, b: Number, options: &Options) -> Result<Number> {
+        let a = Calculator::integer_operand(operator, a)?;
+        let bits = match Calculator::integer_operand(operator, b)? {
+            b if b.is_negative() => {
+                return Err(Error::InvalidOperand(
+                    operator,
+                    Box::new(Number::Integer(b).into()),
+                ))
+            }
+            b => b,
+        };
+        // Shifts that are too large to represent move every bit out of a
+        // right-shifted integer, or past any limit for a left-shifted one.
+        let count = bits.to_u64().unwrap_or(u64::MAX);
+        let result = match operator {
+            Operator::ShiftLeft => {
+                if a.is_zero() {
+                    return Ok(Number::Integer(a));
+                }
+                if a.bits() as u64 + count > MAX_INTEGER_BITS as u64 {
+                    if options.overflow == OverflowPolicy::Unbounded {
+                        return Err(Error::Overflow(operator, vec![a, bits], MAX_INTEGER_BITS));
+                    }
+                    // Any shift past the bounded width behaves the same way
+                    // under a bounded overflow policy.
+                    let result = &a << (BOUNDED_INTEGER_BITS + 1);
+                    return Calculator::apply_overflow_policy(
+                        operator,
+                        vec![a, bits],
+                        result,
+                        options,
+                    );
+                }
+                &a << count as usize
+            }
+            Operator::LogicalShiftRight if a.is_negative() => {
+                let modulus = &BigInt::from(1) << BOUNDED_INTEGER_BITS;
+                let unsigned = &a + &modulus;
+                if &unsigned + &unsigned < modulus {
+                    return Err(Error::InvalidOperand(
+                        operator,
+                        Box::new(Number::Integer(a).into()),
+                    ));
+                }
+                &unsigned >> count.min(BOUNDED_INTEGER_BITS as u64) as usize
+            }
+            Operator::ShiftRight | Operator::LogicalShiftRight => {
+                &a >> count.min(a.bits() as u64 + 1) as usize
+            }
+            _ => unreachable!(), // Only called for shift operators
+        };
+        Calculator::apply_overflow_policy(operator, vec![a, bits], result, options)
+    }
+
+    /// Extracts an integer from an operand, or fails if the operand is not an
+    /// integer, since the operator only makes sense for integers.
+    fn integer_operand(operator: Operator, operand: Number) -> Result<BigInt> {
+        match operand {
+            Number::Integer(integer) => Ok(integer),
+            operand => Err(Error::InvalidOperand(operator, Box::new(operand.into()))),
+        }
+    }
+
+    fn negate(a: Number, options: &Options) -> Result<Number> {
+        match a {
+            Number::Integer(a) => {
+                let result = -a.clone();
+                Calculator::apply_overflow_policy(Operator::Negate, vec![a], result, options)
+            }
+            Number::Rational(a) => Ok(Number::Rational(-a)),
+            Number::Float(a) => Ok(Number::Float(-a)),
+        }
+    }
+
+    /// Decimal literals are tokenized as exact rationals. The numeric mode
+    /// decides whether they remain exact or are approximated with floats.
+    fn apply_numeric_mode(number: Number, options: &Options) -> Number {
+        match (number, options.mode) {
+            (Number::Rational(rational), NumericMode::Float) => Number::Float(rational.to_f64()),
+            (Number::Rational(rational), NumericMode::Rational) => Number::from(rational),
+            (number, _) => number,
+        }
+    }
+
+    /// Applies the numeric mode to a value, leaving booleans unchanged.
+    /// Quantities keep their exact values, so converting between units adds
+    /// no floating point error, and the numeric mode applies when they are
+    /// displayed.
+    fn apply_numeric_mode_to_value(value: Value, options: &Options) -> Value {
+        match value {
+            Value::Number(number) => Value::Number(Calculator::apply_numeric_mode(number, options)),
+            Value::Complex(complex) => Value::from(Complex::new(
+                Calculator::apply_numeric_mode(complex.re, options),
+                Calculator::apply_numeric_mode(complex.im, options),
+            )),
+            value => value,
+        }
+    }
+
+    /// Applies the overflow policy from the options to the exact result of an
+    /// operation. The operands are only used to describe an overflow error.
+    /// Results that fit in a signed integer of `BOUNDED_INTEGER_BITS` bits are
+    /// always returned unchanged.
+    fn apply_overflow_policy(
+        operator: Operator,
+        operands: Vec<BigInt>,
+        result: BigInt,
+        options: &Options,
+    ) -> Result<Number> {
+        if options.overflow == OverflowPolicy::Unbounded {
+            return Ok(Number::Integer(result));
+        }
+        let one = BigInt::from(1);
+        let min = -(&one << (BOUNDED_INTEGER_BITS - 1));
+        let max = &(&one << (BOUNDED_INTEGER_BITS - 1)) - &one;
+        if min <= result && result <= max {
+            return Ok(Number::Integer(result));
+        }
+        match options.overflow {
+            OverflowPolicy::Checked => {
+                Err(Error::Overflow(operator, operands, BOUNDED_INTEGER_BITS))
+            }
+            OverflowPolicy::Wrapping => {
+                let modulus = &one << BOUNDED_INTEGER_BITS;
+                Ok(Number::Integer(
+                    &(&result - &min).rem_euclid(&modulus) + &min,
+                ))
+            }
+            OverflowPolicy::Saturating => Ok(Number::Integer(if result > max { max } else { min })),
+            OverflowPolicy::Unbounded => unreachable!(), // Handled above
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::options::{
+        AngleMode, ComplexFormat, ImplicitMultiplication, RationalFormat, UtcOffset,
+    };
+    use super::types::*;
+    use super::{
+        BigInt, Calculator, Complex, Context, Number, NumericMode, Options, OverflowPolicy, Rates,
+        Rational, Value,
+    };
+
+    /// Evaluates an input string in a new context with the given options.
+    fn evaluate(input: &str, options: &Options) -> Result<Value> {
+        Calculator::evaluate(input, &mut Context::new(options.clone())).map(Option::unwrap)
+    }
+
+    #[test]
+    fn addition_behaves_correctly() {
+        assert_eq!(
+            Calculator::add(1.into(), 2.into(), &Options::default()).unwrap(),
+            3.into()
+        );
+    }
+
+    #[test]
+    fn subtraction_behaves_correctly() {
+        assert_eq!(
+            Calculator::subtract(3.into(), 2.into(), &Options::default()).unwrap(),
+            1.into()
+        );
+    }
+
+    #[test]
+    fn multiplication_behaves_correctly() {
+        assert_eq!(
+            Calculator::multiply(2.into(), 3.into(), &Options::default()).unwrap(),
+            6.into()
+        );
+    }
+
+    #[test]
+    fn division_behaves_correctly() {
+        assert_eq!(
+            Calculator::divide(6.into(), 3.into(), &Options::default()).unwrap(),
+            2.into()
+        );
+    }
+
+    #[test]
+    fn division_behaves_correctly_with_zero_divisor() {
+        assert!(Calculator::divide(1.into(), 0.into(), &Options::default()).is_err());
+    }
+
+    #[test]
+    fn overflow_policy_is_applied_to_results() {
+        let max = Number::Integer(i64::MAX.into());
+        let min = Number::Integer(i64::MIN.into());
+        let past_max = Number::Integer(&BigInt::from(i64::MAX) + &BigInt::from(1));
+        let mut options = Options::default();
+
+        assert_eq!(
+            Calculator::add(max.clone(), 1.into(), &options).unwrap(),
+            past_max
+        );
+
+        options.overflow = OverflowPolicy::Checked;
+        assert!(Calculator::add(max.clone(), 1.into(), &options).is_err());
+        assert!(Calculator::divide(min.clone(), (-1).into(), &options).is_err());
+        assert_eq!(
+            Calculator::add(max.clone(), 0.into(), &options).unwrap(),
+            max
+        );
+
+        options.overflow = OverflowPolicy::Wrapping;
+        assert_eq!(
+            Calculator::add(max.clone(), 1.into(), &options).unwrap(),
+            min
+        );
+        assert_eq!(
+            Calculator::subtract(min.clone(), 1.into(), &options).unwrap(),
+            max
+        );
+
+        options.overflow = OverflowPolicy::Saturating;
+        assert_eq!(
+            Calculator::multiply(max.clone(), 2.into(), &options).unwrap(),
+            max
+        );
+        assert_eq!(
+            Calculator::multiply(min.clone(), 2.into(), &options).unwrap(),
+            min
+        );
+
+        // Integers that skip arithmetic, such as literals and the results of
+        // functions, are subject to the policy as well
+        let literal = "99999999999999999999";
+        let call = "max(99999999999999999999, 1)";
+        options.overflow = OverflowPolicy::Saturating;
+        assert_eq!(evaluate(literal, &options).unwrap(), max);
+        assert_eq!(evaluate(call, &options).unwrap(), max);
+        options.overflow = OverflowPolicy::Wrapping;
+        assert_eq!(
+            evaluate(literal, &options).unwrap(),
+            Number::Integer(BigInt::from(7766279631452241919i64))
+        );
+        options.overflow = OverflowPolicy::Checked;
+        assert_eq!(
+            evaluate(call, &options).unwrap_err().to_string(),
+            "result does not fit in a 64-bit integer: 99999999999999999999"
+        );
+        assert_eq!(evaluate("-9223372036854775808", &options).unwrap(), min);
+    }
+
+    #[test]
+    fn evaluation_behaves_correctly() {
+        let i1 = "3 + 4 * 2";
+        let o1 = Number::from(11);
+
+        let i2 = "3 + 4 / 2";
+        let o2 = Number::from(5);
+
+        assert_eq!(evaluate(i1, &Options::default()).unwrap(), o1);
+        assert_eq!(evaluate(i2, &Options::default()).unwrap(), o2);
+    }
+
+    #[test]
+    fn evaluation_handles_arbitrarily_large_integers() {
+        let i1 = "99999999999 * 99999999999";
+        let o1 = Number::Integer("9999999999800000000001".parse().unwrap());
+
+        let i2 = "123456789012345678901234567890 / 3 - 41152263004115226300411522630";
+        let o2 = Number::from(0);
+
+        assert_eq!(evaluate(i1, &Options::default()).unwrap(), o1);
+        assert_eq!(evaluate(i2, &Options::default()).unwrap(), o2);
+    }
+
+    #[test]
+    fn evaluation_handles_decimals_and_fractional_division() {
+        let options = Options::default();
+        assert_eq!(evaluate("3.5 * 2", &options).unwrap(), Number::Float(7.0));
+        assert_eq!(evaluate(".5 + 5.", &options).unwrap(), Number::Float(5.5));
+        assert_eq!(evaluate("7 / 2", &options).unwrap(), Number::Float(3.5));
+        assert_eq!(evaluate("8 / 2", &options).unwrap(), Number::from(4));
+    }
+
+    #[test]
+    fn float_results_fail_when_too_large_for_a_float() {
+        let options = Options::default();
+        for input in [
+            "10^400 / 3",
+            "10^400 + 0.5",
+            "10^400 * 0.5",
+            "1e308 * 10",
+            "1.5e308^2",
+        ] {
+            assert!(matches!(
+                evaluate(input, &options),
+                Err(Error::FloatOverflow(_, _, _))
+            ));
+        }
+        assert!(matches!(
+            evaluate("1e400", &options),
+            Err(Error::InvalidExpression(_))
+        ));
+        assert_eq!(evaluate("1e-400", &options).unwrap(), Number::Float(0.0));
+        // Infinity is only a result when it goes in
+        assert_eq!(
+            evaluate("inf + 1", &options).unwrap(),
+            Number::Float(f64::INFINITY)
+        );
+        let options = Options {
+            mode: NumericMode::Rational,
+            ..Options::default()
+        };
+        assert!(evaluate("10^400 / 3", &options).is_ok());
+    }
+
+    #[test]
+    fn scientific_notation_reads_back_as_the_float_it_prints() {
+        let options = Options::default();
+        assert_eq!(evaluate("1e3", &options).unwrap(), Number::Float(1000.0));
+        assert_eq!(evaluate("2.5e-3", &options).unwrap(), Number::Float(0.0025));
+        assert_eq!(evaluate("1e+2 m", &options).unwrap().to_string(), "100 m");
+        for input in ["2.0^60", "1 / 3 / 10^7", "-(2.0^-30)"] {
+            let printed = evaluate(input, &options).unwrap().to_string();
+            assert!(printed.contains('e'));
+            assert_eq!(
+                evaluate(&printed, &options).unwrap(),
+                evaluate(input, &options).unwrap()
+            );
+        }
+        assert!(evaluate("2e", &options).is_err());
+        assert!(evaluate("2e-", &options).is_err());
+    }
+
+    #[test]
+    fn evaluation_is_exact_in_rational_mode() {
+        let options = Options {
+            mode: NumericMode::Rational,
+            ..Options::default()
+        };
+        let half = Number::Rational(Rational::new(1.into(), 2.into()));
+        assert_eq!(evaluate("1/3 + 1/6", &options).unwrap(), half);
+        assert_eq!(evaluate("0.1 * 5", &options).unwrap(), half);
+        assert_eq!(
+            evaluate("7 / 2 + 7 / 2", &options).unwrap(),
+            Number::from(7)
+        );
+        assert_eq!(
+            evaluate("0.1 + 0.2 - 0.3", &options).unwrap(),
+            Number::from(0)
+        );
+    }
+
+    #[test]
+    fn rational_results_are_formatted_according_to_options() {
+        let mut options = Options {
+            mode: NumericMode::Rational,
+            ..Options::default()
+        };
+        let result = evaluate("7 / 3", &options).unwrap();
+        assert_eq!(result.format(&options), "7/3");
+
+        options.rational_format = RationalFormat::Mixed;
+        assert_eq!(result.format(&options), "2 1/3");
+
+        options.rational_format = RationalFormat::Decimal;
+        options.digits = 3;
+        assert_eq!(result.format(&options), "2.333");
+    }
+
+    #[test]
+    fn evaluation_handles_unary_operators() {
+        let options = Options::default();
+        assert_eq!(evaluate("-5 + 3", &options).unwrap(), Number::from(-2));
+        assert_eq!(evaluate("2 * -4", &options).unwrap(), Number::from(-8));
+        assert_eq!(evaluate("-(1+2)", &options).unwrap(), Number::from(-3));
+        assert_eq!(evaluate("3 - -+2", &options).unwrap(), Number::from(5));
+        assert_eq!(evaluate("-2 * 3 + 10", &options).unwrap(), Number::from(4));
+    }
+
+    #[test]
+    fn evaluation_groups_same_precedence_operators_from_the_left() {
+        let options = Options::default();
+        assert_eq!(evaluate("8 - 4 - 2", &options).unwrap(), Number::from(2));
+        assert_eq!(evaluate("16 / 4 / 2", &options).unwrap(), Number::from(2));
+        assert_eq!(
+            evaluate("2 * 6 / 3 * 2", &options).unwrap(),
+            Number::from(8)
+        );
+        assert_eq!(
+            evaluate("10 - 2 + 3 - 1", &options).unwrap(),
+            Number::from(10)
+        );
+    }
+
+    #[test]
+    fn evaluation_handles_exponentiation() {
+        let options = Options::default();
+        assert_eq!(evaluate("2^3^2", &options).unwrap(), Number::from(512));
+        assert_eq!(
+            evaluate("2 ** 10 - 1", &options).unwrap(),
+            Number::from(1023)
+        );
+        assert_eq!(evaluate("-2^2", &options).unwrap(), Number::from(-4));
+        assert_eq!(evaluate("2 * 3^2", &options).unwrap(), Number::from(18));
+        assert_eq!(evaluate("2^-2", &options).unwrap(), Number::Float(0.25));
+        assert_eq!(evaluate("4^0.5", &options).unwrap(), Number::Float(2.0));
+        assert_eq!(
+            evaluate("(-1)^1000001", &options).unwrap(),
+            Number::from(-1)
+        );
+        assert!(evaluate("3^100000000", &options).is_err());
+    }
+
+    #[test]
+    fn exponentiation_respects_numeric_mode_and_overflow_policy() {
+        let mut options = Options {
+            mode: NumericMode::Rational,
+            ..Options::default()
+        };
+        assert_eq!(
+            evaluate("2^-2", &options).unwrap(),
+            Number::Rational(Rational::new(1.into(), 4.into()))
+        );
+        assert_eq!(
+            evaluate("(2/3)^3", &options).unwrap(),
+            Number::Rational(Rational::new(8.into(), 27.into()))
+        );
+        assert!(evaluate("0^-1", &options).is_err());
+        // A zero base fails with a negative exponent in every mode, and a
+        // power that is not a real number is complex instead of being NaN
+        for mode in [NumericMode::Float, NumericMode::Rational] {
+            let options = Options {
+                mode,
+                ..Options::default()
+            };
+            for input in ["0^-1", "0.0^-2", "pow(0, -0.5)"] {
+                assert!(matches!(
+                    evaluate(input, &options),
+                    Err(Error::DivideByZero(Operator::Power, _, _))
+                ));
+            }
+            for input in ["(-4)^0.5", "pow(-4, 0.5)"] {
+                assert_eq!(evaluate(input, &options).unwrap().to_string(), "2i");
+            }
+        }
+
+        options.overflow = OverflowPolicy::Checked;
+        assert!(evaluate("2^63", &options).is_err());
+        assert!(evaluate("3^100000000", &options).is_err());
+
+        options.overflow = OverflowPolicy::Wrapping;
+        assert_eq!(evaluate("2^64 + 5", &options).unwrap(), Number::from(5));
+        assert_eq!(evaluate("2^100000000", &options).unwrap(), Number::from(0));
+
+        options.overflow = OverflowPolicy::Saturating;
+        assert_eq!(
+            evaluate("(-3)^100000001", &options).unwrap(),
+            Number::Integer(i64::MIN.into())
+        );
+    }
+
+    #[test]
+    fn evaluation_handles_remainder_modulo_and_floor_division() {
+        let options = Options::default();
+        let cases = [
+            ("7 % 3", Number::from(1)),
+            ("-7 % 3", Number::from(-1)),
+            ("7 % -3", Number::from(1)),
+            ("-7 mod 3", Number::from(2)),
+            ("7 mod -3", Number::from(1)),
+            ("-7 mod -3", Number::from(2)),
+            ("7 // 2", Number::from(3)),
+            ("-7 // 2", Number::from(-4)),
+            ("7.5 % 2", Number::Float(1.5)),
+            ("-7.5 mod 2", Number::Float(0.5)),
+            ("-7.5 // 2", Number::Float(-4.0)),
+            ("2 + 17 mod 5 * 2", Number::from(6)),
+        ];
+        for (input, output) in cases {
+            assert_eq!(evaluate(input, &options).unwrap(), output);
+        }
+        assert!(evaluate("7 % 0", &options).is_err());
+        assert!(evaluate("7 mod 0", &options).is_err());
+        assert!(evaluate("7 // 0.0", &options).is_err());
+    }
+
+    #[test]
+    fn remainder_modulo_and_floor_division_are_exact_in_rational_mode() {
+        let options = Options {
+            mode: NumericMode::Rational,
+            ..Options::default()
+        };
+        let half = Number::Rational(Rational::new(1.into(), 2.into()));
+        assert_eq!(evaluate("7/2 mod 1", &options).unwrap(), half);
+        assert_eq!(
+            evaluate("-7/2 % 1", &options).unwrap(),
+            Number::Rational(Rational::new((-1).into(), 2.into()))
+        );
+        assert_eq!(evaluate("-7/2 mod -1", &options).unwrap(), half);
+        assert_eq!(evaluate("-7/2 // 1", &options).unwrap(), Number::from(-4));
+    }
+
+    #[test]
+    fn evaluation_handles_bitwise_operators() {
+        let options = Options::default();
+        let cases = [
+            ("12 & 10", 8),
+            ("12 | 3", 15),
+            ("12 xor 10", 6),
+            ("~5", -6),
+            ("1 << 10", 1024),
+            ("-16 >> 2", -4),
+            ("-1 >> 100", -1),
+            ("-1 >>> 60", 15),
+            ("16 >>> 2", 4),
+            ("1 << 2 + 1", 8),
+            ("6 & 3 | 8", 10),
+            ("1 | 6 xor 3 & 5", 7),
+            ("~0 & 255", 255),
+        ];
+        for (input, output) in cases {
+            assert_eq!(evaluate(input, &options).unwrap(), Number::from(output));
+        }
+        assert!(evaluate("1.5 & 1", &options).is_err());
+        assert!(evaluate("1 << -1", &options).is_err());
+        assert!(evaluate("1 << 100000000", &options).is_err());
+        // Only negative integers are reinterpreted as unsigned
+        assert_eq!(
+            evaluate("2^100 >>> 1", &options).unwrap(),
+            evaluate("2^99", &options).unwrap()
+        );
+        assert_eq!(
+            evaluate("-1 >>> 0", &options).unwrap(),
+            evaluate("2^64 - 1", &options).unwrap()
+        );
+        assert_eq!(evaluate("-2^63 >>> 63", &options).unwrap(), Number::from(1));
+        assert!(evaluate("-2^63 - 1 >>> 1", &options).is_err());
+    }
+
+    #[test]
+    fn evaluation_handles_function_calls() {
+        let options = Options::default();
+        let cases = [
+            ("sqrt(16)", Number::from(4)),
+            ("sqrt(2)", Number::Float(2f64.sqrt())),
+            ("max(3, 7, 2)", Number::from(7)),
+            ("min(3, -7, 2)", Number::from(-7)),
+            ("abs(-4)", Number::from(4)),
+            ("pow(2, 10)", Number::from(1024)),
+            ("floor(-2.5)", Number::from(-3)),
+            ("ceil(-2.5)", Number::from(-2)),
+            ("round(2.5)", Number::from(3)),
+            ("log(1000)", Number::Float(3.0)),
+            ("log(8, 2)", Number::Float(3.0)),
+            ("ln(1)", Number::Float(0.0)),
+            ("exp(0)", Number::Float(1.0)),
+            ("1 + sqrt(max(9, 4 * 4)) * 2", Number::from(9)),
+            ("-abs(3 - 5)", Number::from(-2)),
+            ("2 ^ sqrt (4)", Number::from(4)),
+        ];
+        for (input, output) in cases {
+            assert_eq!(evaluate(input, &options).unwrap(), output);
+        }
+
+        let options = Options {
+            mode: NumericMode::Rational,
+            ..Options::default()
+        };
+        assert_eq!(
+            evaluate("sqrt(9 / 4)", &options).unwrap(),
+            Number::Rational(Rational::new(3.into(), 2.into()))
+        );
+        assert_eq!(
+            evaluate("round(-7 / 2)", &options).unwrap(),
+            Number::from(-4)
+        );
+    }
+
+    #[test]
+    fn function_calls_fail_with_invalid_names_arguments_or_commas() {
+        let options = Options::default();
+        assert!(matches!(
+            evaluate("foo(1)", &options),
+            Err(Error::UnknownFunction(name, 0)) if name == "foo"
+        ));
+        assert!(matches!(
+            evaluate("1 + sqrt(1, 2)", &options),
+            Err(Error::ArityMismatch(name, Arity::Exactly(1), 2, 4)) if name == "sqrt"
+        ));
+        assert!(matches!(
+            evaluate("max()", &options),
+            Err(Error::ArityMismatch(_, Arity::AtLeast(1), 0, _))
+        ));
+        assert!(matches!(
+            evaluate("ln(0)", &options),
+            Err(Error::InvalidArgument(_, _))
+        ));
+        assert!(evaluate("log(0)", &options).is_err());
+        assert!(evaluate("log(8, 1)", &options).is_err());
+        assert!(evaluate("1, 2", &options).is_err());
+        assert!(evaluate("max((1, 2) + 3)", &options).is_err());
+        assert!(evaluate("max(1, )", &options).is_err());
+        assert!(evaluate("max(, 1)", &options).is_err());
+        assert!(evaluate("2 sqrt(4)", &options).is_err());
+        assert!(evaluate("sqrt", &options).is_err());
+        assert!(evaluate("()", &options).is_err());
+    }
+
+    #[test]
+    fn evaluation_handles_trigonometric_functions_in_each_angle_mode() {
+        let mut options = Options::default();
+        let cases = [
+            ("sin(0)", 0.0),
+            ("cos(0)", 1.0),
+            ("atan(1) * 4", std::f64::consts::PI),
+            ("sinh(0) + cosh(0)", 1.0),
+            ("tanh(0)", 0.0),
+            ("acosh(1)", 0.0),
+        ];
+        for (input, output) in cases {
+            assert_eq!(evaluate(input, &options).unwrap(), Number::Float(output));
+        }
+
+        options.angle = AngleMode::Degrees;
+        let cases = [
+            ("sin(180)", 0.0),
+            ("sin(-90)", -1.0),
+            ("cos(90)", 0.0),
+            ("cos(720)", 1.0),
+            ("tan(45)", 1.0),
+            ("tan(-45)", -1.0),
+            ("tan(180)", 0.0),
+            ("asin(1)", 90.0),
+            ("asin(0.5)", 30.0),
+            ("asin(-0.5)", -30.0),
+            ("acos(-1)", 180.0),
+            ("acos(0.5)", 60.0),
+            ("atan(-1)", -45.0),
+            ("sin(30)", 0.5),
+            ("cos(120)", -0.5),
+            ("sin(60)", 60f64.to_radians().sin()),
+        ];
+        for (input, output) in cases {
+            assert_eq!(evaluate(input, &options).unwrap(), Number::Float(output));
+        }
+        assert!(evaluate("tan(90)", &options).is_err());
+        assert!(evaluate("asin(2)", &options).is_err());
+        assert!(evaluate("atanh(1)", &options).is_err());
+
+        options.angle = AngleMode::Gradians;
+        assert_eq!(evaluate("cos(200)", &options).unwrap(), Number::Float(-1.0));
+        assert_eq!(evaluate("atan(1)", &options).unwrap(), Number::Float(50.0));
+    }
+
+    #[test]
+    fn evaluation_handles_named_constants() {
+        let options = Options::default();
+        let cases = [
+            ("2 * pi * 5", 2.0 * std::f64::consts::PI * 5.0),
+            ("tau - 2 * pi", 0.0),
+            ("ln(e)", 1.0),
+            ("phi", (1.0 + 5f64.sqrt()) / 2.0),
+            ("-inf", f64::NEG_INFINITY),
+        ];
+        for (input, output) in cases {
+            assert_eq!(evaluate(input, &options).unwrap(), Number::Float(output));
+        }
+        assert!(matches!(
+            evaluate("2 * tau2", &options),
+            Err(Error::UnknownVariable(name, 4)) if name == "tau2"
+        ));
+        assert!(evaluate("2 pi", &options).is_err());
+    }
+
+    #[test]
+    fn constants_are_computed_to_the_requested_precision_in_rational_mode() {
+        let mut options = Options {
+            mode: NumericMode::Rational,
+            rational_format: RationalFormat::Decimal,
+            digits: 50,
+            ..Options::default()
+        };
+        let cases = [
+            ("pi", "3.14159265358979323846264338327950288419716939937511"),
+            (
+                "tau",
+                "6.28318530717958647692528676655900576839433879875021",
+            ),
+            ("e", "2.71828182845904523536028747135266249775724709369996"),
+            (
+                "phi",
+                "1.61803398874989484820458683436563811772030917980576",
+            ),
+        ];
+        for (input, output) in cases {
+            let result = evaluate(input, &options).unwrap();
+            assert_eq!(result.format(&options), output);
+        }
+
+        options.digits = 3;
+        let result = evaluate("2 * pi * 5", &options).unwrap();
+        assert_eq!(result.format(&options), "31.416");
+    }
+
+    #[test]
+    fn evaluation_assigns_and_reads_variables_in_a_context() {
+        let mut context = Context::default();
+        assert_eq!(
+            Calculator::evaluate("rate = 7 / 100", &mut context).unwrap(),
+            Some(Number::Float(0.07).into())
+        );
+        assert_eq!(
+            Calculator::evaluate("1000 * (1 + rate)", &mut context).unwrap(),
+            Some(Number::Float(1000.0 * (1.0 + 0.07)).into())
+        );
+        assert_eq!(
+            Calculator::evaluate("x = -2; y = x * x; y - x", &mut context).unwrap(),
+            Some(6.into())
+        );
+        assert_eq!(context.resolve("y"), Some(&4.into()));
+        assert_eq!(
+            context
+                .variables()
+                .map(|(name, _)| name)
+                .collect::<Vec<_>>(),
+            vec!["rate", "x", "y"]
+        );
+        assert!(context.take_warnings().is_empty());
+
+        assert_eq!(context.remove_variable("x"), Some((-2).into()));
+        assert!(matches!(
+            Calculator::evaluate("y + x", &mut context),
+            Err(Error::UnknownVariable(name, 4)) if name == "x"
+        ));
+        assert!(Calculator::evaluate("x + 1 = 2", &mut context).is_err());
+        assert!(Calculator::evaluate("x = y = 2", &mut context).is_err());
+        assert!(Calculator::evaluate(";", &mut context).is_err());
+    }
+
+    #[test]
+    fn variables_shadow_constants_with_a_warning() {
+        let mut context = Context::default();
+        Calculator::evaluate("pi = 3", &mut context).unwrap();
+        assert_eq!(
+            Calculator::evaluate("2 * pi", &mut context).unwrap(),
+            Some(6.into())
+        );
+        assert_eq!(context.take_warnings().len(), 1);
+        assert!(context.take_warnings().is_empty());
+
+        context.remove_variable("pi");
+        assert_eq!(
+            Calculator::evaluate("pi", &mut context).unwrap(),
+            Some(Number::Float(std::f64::consts::PI).into())
+        );
+    }
+
+    #[test]
+    fn evaluation_defines_and_calls_user_functions() {
+        let mut context = Context::default();
+        assert_eq!(
+            Calculator::evaluate("f(x, y) = x^2 + y", &mut context).unwrap(),
+            None
+        );
+        assert_eq!(
+            Calculator::evaluate("f(3, 4)", &mut context).unwrap(),
+            Some(13.into())
+        );
+
+        // Names in the body are looked up when the function is called
+        Calculator::evaluate("g(x) = x * scale + h(x)", &mut context).unwrap();
+        Calculator::evaluate("scale = 10; h(y) = -y", &mut context).unwrap();
+        assert_eq!(
+            Calculator::evaluate("g(2)", &mut context).unwrap(),
+            Some(18.into())
+        );
+        Calculator::evaluate("scale = 100", &mut context).unwrap();
+        assert_eq!(
+            Calculator::evaluate("f(g(1), 1)", &mut context).unwrap(),
+            Some(9802.into())
+        );
+
+        // Parameters are only visible inside the function that declares them
+        Calculator::evaluate("k(x) = y", &mut context).unwrap();
+        assert!(matches!(
+            Calculator::evaluate("h(k(1))", &mut context),
+            Err(Error::UnknownVariable(name, _)) if name == "y"
+        ));
+        assert_eq!(
+            context
+                .functions()
+                .map(|(name, _)| name)
+                .collect::<Vec<_>>(),
+            vec!["f", "g", "h", "k"]
+        );
+        assert!(context.remove_function("k"));
+    }
+
+    #[test]
+    fn user_functions_fail_with_invalid_definitions_calls_or_recursion() {
+        let mut context = Context::default();
+        Calculator::evaluate("f(x, y) = x + y", &mut context).unwrap();
+        assert!(matches!(
+            Calculator::evaluate("1 + f(1)", &mut context),
+            Err(Error::ArityMismatch(name, Arity::Exactly(2), 1, 4)) if name == "f"
+        ));
+        assert!(matches!(
+            Calculator::evaluate("g(1)", &mut context),
+            Err(Error::UnknownFunction(name, 0)) if name == "g"
+        ));
+
+        Calculator::evaluate("loop(x) = loop(x + 1)", &mut context).unwrap();
+        assert!(matches!(
+            Calculator::evaluate("loop(0)", &mut context),
+            Err(Error::RecursionLimit(name, 10)) if name == "loop"
+        ));
+
+        assert!(Calculator::evaluate("sqrt(x) = x", &mut context).is_err());
+        assert!(Calculator::evaluate("f(x, x) = x", &mut context).is_err());
+        assert!(Calculator::evaluate("f(1) = 1", &mut context).is_err());
+        assert!(Calculator::evaluate("f(x,) = x", &mut context).is_err());
+        assert!(Calculator::evaluate("f(x) = x +", &mut context).is_err());
+        assert!(Calculator::evaluate("f(x) + 1 = x", &mut context).is_err());
+    }
+
+    #[test]
+    fn evaluation_handles_implicit_multiplication_when_enabled() {
+        let mut options = Options::default();
+        for input in ["2(3+4)", "(1+2)(3+4)", "3pi", "(2)3", "2 sqrt(4)"] {
+            assert!(evaluate(input, &options).is_err());
+        }
+
+        options.implicit_multiplication = ImplicitMultiplication::Standard;
+        let cases = [
+            ("2(3+4)", 14),
+            ("(1+2)(3+4)", 21),
+            ("(2)3", 6),
+            ("2 sqrt(16)", 8),
+            ("2(3)^2", 18),
+            ("-2(3)", -6),
+            ("12 / 2(3)", 18),
+        ];
+        for (input, output) in cases {
+            assert_eq!(evaluate(input, &options).unwrap(), Number::from(output));
+        }
+        assert_eq!(
+            evaluate("3pi", &options).unwrap(),
+            Number::Float(3.0 * std::f64::consts::PI)
+        );
+
+        options.implicit_multiplication = ImplicitMultiplication::Tight;
+        assert_eq!(evaluate("12 / 2(3)", &options).unwrap(), Number::from(2));
+        assert_eq!(evaluate("2(3)^2", &options).unwrap(), Number::from(18));
+
+        let mut context = Context::new(options);
+        Calculator::evaluate("x = 4; f(y) = 2y", &mut context).unwrap();
+        assert_eq!(
+            Calculator::evaluate("1 / 2x + f(3)x", &mut context).unwrap(),
+            Some(Number::Float(1.0 / 8.0 + 24.0).into())
+        );
+    }
+
+    #[test]
+    fn evaluation_handles_postfix_operators() {
+        let mut options = Options::default();
+        let cases = [
+            ("0!", Number::from(1)),
+            ("5!", 120.into()),
+            ("3!!", 3.into()),
+            ("8!!", 384.into()),
+            ("0!!", 1.into()),
+            ("-3!", (-6).into()),
+            ("2^3!", 64.into()),
+            ("(1 + 2)! * 2", 12.into()),
+            ("3!! !", 6.into()),
+            ("50%", Number::Float(0.5)),
+            ("200%", 2.into()),
+            ("50% * 4", 2.into()),
+            ("max(50%, 1%)", Number::Float(0.5)),
+            ("50% - 1", Number::Float(-0.5)),
+            ("7 % -3", 1.into()),
+            ("7 % 3", 1.into()),
+            ("50% mod 1", Number::Float(0.5)),
+        ];
+        for (input, output) in cases {
+            assert_eq!(evaluate(input, &options).unwrap(), output);
+        }
+        assert_eq!(
+            evaluate("30!", &options).unwrap().to_string(),
+            "265252859812191058636308480000000"
+        );
+        for (input, output) in [
+            ("10 % -3", 1),
+            ("10 % +3", 1),
+            ("10 % ~3", 2),
+            ("10 % ~ 3", 2),
+        ] {
+            assert_eq!(evaluate(input, &options).unwrap(), Number::from(output));
+        }
+        for input in ["10% - 3", "10 % - 3", "10%- 3"] {
+            assert_eq!(evaluate(input, &options).unwrap(), Number::Float(-2.9));
+        }
+        assert!(evaluate("(-1)!", &options).is_err());
+        assert!(evaluate("2.5!", &options).is_err());
+        assert!(evaluate("100000!", &options).is_err());
+        assert!(evaluate("3!2", &options).is_err());
+        assert!(evaluate("!3", &options).is_err());
+        assert!(evaluate("3! (2)", &options).is_err());
+
+        options.overflow = OverflowPolicy::Checked;
+        assert_eq!(
+            evaluate("20!", &options).unwrap().to_string(),
+            "2432902008176640000"
+        );
+        assert!(evaluate("21!", &options).is_err());
+        options.overflow = OverflowPolicy::Wrapping;
+        assert_eq!(evaluate("100!", &options).unwrap(), Value::from(0));
+        assert_eq!(
+            evaluate("21!", &options).unwrap().to_string(),
+            "-4249290049419214848"
+        );
+
+        options.implicit_multiplication = ImplicitMultiplication::Standard;
+        assert_eq!(evaluate("3!(2)", &options).unwrap(), Value::from(12));
+    }
+
+    #[test]
+    fn evaluation_handles_percentages_relative_to_the_left_operand() {
+        let options = Options::default();
+        let cases = [
+            ("200 + 15%", 230.into()),
+            ("200 - 15%", 170.into()),
+            ("50 + 10%", 55.into()),
+            ("200 + -15%", 170.into()),
+            ("200 * 15%", 30.into()),
+            ("30 / 15%", 200.into()),
+            ("20% of 80", 16.into()),
+            ("20% of 80 + 1", 17.into()),
+            ("100 + 10% + 10%", 121.into()),
+            ("100 + (10% + 10%)", 120.into()),
+            ("10 + 15%", Number::Float(11.5)),
+            ("15% + 10", Number::Float(10.15)),
+            ("25% - 5%", Number::Float(0.2)),
+            ("0.5 of 10", Number::Float(5.0)),
+            ("sqrt(25%)", Number::Float(0.5)),
+        ];
+        for (input, output) in cases {
+            assert_eq!(evaluate(input, &options).unwrap(), output);
+        }
+        assert!(evaluate("20% of", &options).is_err());
+        assert!(evaluate("of 80", &options).is_err());
+
+        let options = Options {
+            mode: NumericMode::Rational,
+            ..Options::default()
+        };
+        assert_eq!(
+            evaluate("10 + 15%", &options).unwrap(),
+            Number::Rational(Rational::new(23.into(), 2.into()))
+        );
+    }
+
+    #[test]
+    fn comparisons_and_logical_operators_yield_booleans() {
+        let options = Options::default();
+        let cases = [
+            ("3 > 2", true),
+            ("3 >= 4", false),
+            ("2 < 1", false),
+            ("1 <= 1", true),
+            ("1 == 1.0", true),
+            ("5 != 5", false),
+            ("1/3 == 2/6", true),
+            ("1 + 2 == 3", true),
+            ("1 << 2 <= 4", true),
+            ("1 < 2 == 2 < 3", true),
+            ("50% < 1", true),
+            ("true == false", false),
+            ("3 > 2 and 2 > 1", true),
+            ("2 < 1 or not false", true),
+            ("not 1 == 2", true),
+            ("true or false and false", true),
+            ("inf - inf == inf - inf", false),
+            ("inf - inf != inf - inf", true),
+        ];
+        for (input, output) in cases {
+            assert_eq!(evaluate(input, &options).unwrap(), Value::Boolean(output));
         }
-        Ok(())
+        assert_eq!(evaluate("3 > 2", &options).unwrap().to_string(), "true");
+        assert!(evaluate("true + 1", &options).is_err());
+        assert!(evaluate("true == 1", &options).is_err());
+        assert!(evaluate("true < false", &options).is_err());
+        assert!(evaluate("1 and true", &options).is_err());
+        assert!(evaluate("not 0", &options).is_err());
+        assert!(evaluate("sqrt(true)", &options).is_err());
+        assert!(evaluate("1 < 2 < 3", &options).is_err());
     }
 
-    /// Converts an expression from infix notation (2 + 2) to Reverse Polish Notation (RPN),
-    /// otherwise known as postfix notation (2 2 +), using the shunting-yard algorithm:
-    /// https://en.wikipedia.org/wiki/Shunting-yard_algorithm. This function performs
-    /// no validation beyond parentheses matching; it assumes that the input infix expression is valid.
-    fn marshal_infix_expression_to_postfix(expression: Vec<Token>) -> Result<Vec<Token>> {
-        let precedence_map = HashMap::from([
-            (Operator::Add, 1),
-            (Operator::Subtract, 1),
-            (Operator::Multiply, 2),
-            (Operator::Divide, 2),
-        ]); // No good way to make this static without another crate
-        let mut output: Vec<Token> = vec![];
-        let mut operator_stack: Vec<Token> = vec![];
-        'expression_loop: for token in expression {
-            match token {
-                Token::Operand(operand) => output.push(Token::Operand(operand)),
-                Token::LeftParen => operator_stack.push(Token::LeftParen),
-                Token::Operator(operator_cur) => {
-                    while let Some(operator_prev) = operator_stack.last() {
-                        match operator_prev {
-                            Token::LeftParen => break,
-                            Token::Operator(operator_top) => {
-                                if precedence_map[operator_top] > precedence_map[&operator_cur] {
-                                    output.push(operator_stack.pop().unwrap()); // Guaranteed to exist because of while let condition
-                                } else {
-                                    break;
-                                }
-                            }
-                            _ => unreachable!(),
-                        }
-                    }
-                    operator_stack.push(Token::Operator(operator_cur));
-                }
-                Token::RightParen => {
-                    while let Some(operator_prev) = operator_stack.last() {
-                        match operator_prev {
-                            Token::Operator(_) => {
-                                output.push(operator_stack.pop().unwrap()); // Guaranteed to exist because of while let condition
-                            }
-                            Token::LeftParen => {
-                                operator_stack.pop().unwrap(); // Guaranteed to exist because of while let condition
-                                continue 'expression_loop;
-                            }
-                            _ => unreachable!(),
-                        }
-                    }
-                    return Err(Error::MismatchedParentheses);
-                }
-            }
-        }
-        while let Some(operator) = operator_stack.pop() {
-            if operator.eq(&Token::LeftParen) {
-                return Err(Error::MismatchedParentheses);
-            }
-            output.push(operator);
+    #[test]
+    fn conditional_expressions_evaluate_only_the_chosen_value() {
+        let options = Options::default();
+        let cases = [
+            ("x = -5; x > 0 ? x : -x", 5.into()),
+            ("false ? 1/0 : 2", 2.into()),
+            ("true ? 2 : 1/0", 2.into()),
+            ("1 == 1 ? 5 : 6 + 1", 5.into()),
+            ("a = 2; a > 1 ? a < 3 ? 10 : 20 : 30", 10.into()),
+            ("a = 4; a > 1 ? a < 3 ? 10 : 20 : 30", 20.into()),
+            ("false ? 1 : false ? 2 : 3", 3.into()),
+            ("max(1 > 0 ? 3 : 4, 2)", 3.into()),
+            ("2 * (1 < 0 ? 1 : 2)", 4.into()),
+            ("f(n) = n <= 1 ? 1 : n * f(n - 1); f(10)", 3628800.into()),
+        ];
+        for (input, output) in cases {
+            assert_eq!(evaluate(input, &options).unwrap(), Value::Number(output));
         }
-        Ok(output)
+        assert!(evaluate("1 ? 2 : 3", &options).is_err());
+        assert!(evaluate("true ? 2", &options).is_err());
+        assert!(evaluate("(true ? 2) : 3", &options).is_err());
+        assert!(evaluate("1 : 2", &options).is_err());
+        assert!(evaluate("true ? : 2", &options).is_err());
     }
 
-    /// Evalutes an expression ordered using Reverse Polish Notation (RPN),
-    /// otherwise known as postfix notation. The algorithm proceeds by pushing
-    /// each encountered operand onto a stack, popping the last two off and
-    /// calculating a result for each operator encountered, and then pushing
-    /// the result back on to the stack. Assuming an input with `n` operands
-    /// and `n - 1` operators, in proper order, the output will be the sole
-    /// remaining value on the stack after all tokens have been spent.
-    ///
-    /// Note: This function assumes that the input expression represents a valid
-    /// postfix notation expression. It performs no validation on the input,
-    /// and it will panic if the input is invalid (e.g., '+ 3 2`). It will also
-    /// panic on a zero-length expression. As such, input should be validated before
-    /// being passed to this function.
-    fn evaluate_postfix_expression(expression: Vec<Token>) -> Result<isize> {
-        let mut operand_stack: Vec<isize> = vec![];
-        for token in expression {
-            match token {
-                Token::Operand(operand) => operand_stack.push(operand),
-                Token::Operator(operator) => {
-                    let b = operand_stack.pop().unwrap_or_else(|| {
-                        panic!("Found no operands for operator {:?}.", &operator)
-                    });
-                    let a = operand_stack.pop().unwrap_or_else(|| {
-                        panic!("Found only one operand for operator {:?}: {}", &operator, b)
-                    });
-                    let result = match operator {
-                        Operator::Add => Calculator::add(a, b),
-                        Operator::Subtract => Calculator::subtract(a, b),
-                        Operator::Multiply => Calculator::multiply(a, b),
-                        Operator::Divide => Calculator::divide(a, b)?,
-                    };
-                    operand_stack.push(result)
-                }
-                _ => unreachable!(), // Postfix notation does not use parentheses
-            }
+    #[test]
+    fn complex_arithmetic_is_exact_for_exact_parts() {
+        let options = Options::default();
+        let cases = [
+            ("(1+2i)*(3-i)", "5+5i"),
+            ("sqrt(-4)", "2i"),
+            ("i * i", "-1"),
+            ("2i^2", "-4"),
+            ("1/i", "-i"),
+            ("(1+2i)/(3-i)", "0.1+0.7i"),
+            ("(1+i)^10", "32i"),
+            ("(1+i)^-2", "-0.5i"),
+            ("-(1+i)", "-1-i"),
+            ("0.5i + 1", "1+0.5i"),
+            ("abs(3+4i)", "5"),
+            ("conj(1+2i)", "1-2i"),
+            ("re(1+2i) + im(1+2i)", "3"),
+            ("sqrt(2i)", "1+i"),
+            ("ln(-1)", "3.141592653589793i"),
+            ("log(-100)", "2+1.3643763538418412i"),
+            ("log(-8, 2)", "3+4.532360141827194i"),
+            ("(-4)^0.5", "2i"),
+            ("(-4)^-1.5", "0.125i"),
+            ("pow(-9, 1/2)", "3i"),
+            ("x = 1+i; x * x", "2i"),
+        ];
+        for (input, output) in cases {
+            assert_eq!(evaluate(input, &options).unwrap().to_string(), output);
         }
-        Ok(operand_stack.pop().expect(
-            "No result value found. Please make sure the input expression has non-zero length.",
-        ))
+        assert_eq!(
+            evaluate("(1+2i)*(3-i)", &options).unwrap(),
+            Value::Complex(Complex::new(5.into(), 5.into()))
+        );
+        assert_eq!(
+            evaluate("(1+2i)*(3-i) == 5+5i", &options).unwrap(),
+            Value::Boolean(true)
+        );
+        assert_eq!(evaluate("i == 1", &options).unwrap(), Value::Boolean(false));
+        assert!(evaluate("i < 1", &options).is_err());
+        assert!(evaluate("max(i, 1)", &options).is_err());
+        assert!(evaluate("(1+i) % 2", &options).is_err());
+        assert!(matches!(
+            evaluate("(1+i)/0", &options),
+            Err(Error::DivideByZero(_, _, _))
+        ));
+
+        let options = Options {
+            mode: NumericMode::Rational,
+            ..Options::default()
+        };
+        assert_eq!(
+            evaluate("(1+2i)/(3-i)", &options).unwrap(),
+            Value::Complex(Complex::new(
+                Number::Rational(Rational::new(1.into(), 10.into())),
+                Number::Rational(Rational::new(7.into(), 10.into()))
+            ))
+        );
     }
 
-    fn add(a: isize, b: isize) -> isize {
-        a + b
+    #[test]
+    fn complex_numbers_can_be_written_and_displayed_in_polar_form() {
+        let mut options = Options {
+            angle: AngleMode::Degrees,
+            ..Options::default()
+        };
+        assert_eq!(evaluate("2∠90", &options).unwrap().to_string(), "2i");
+        assert_eq!(evaluate("2∠180", &options).unwrap(), Number::Float(-2.0));
+        assert_eq!(evaluate("2 * 1∠-90", &options).unwrap().to_string(), "-2i");
+        assert_eq!(evaluate("arg(-i)", &options).unwrap(), Number::Float(-90.0));
+
+        options.complex_format = ComplexFormat::Polar;
+        let result = evaluate("3+3i", &options).unwrap();
+        assert_eq!(result.format(&options), "4.242640687119285∠45");
+        assert_eq!(evaluate("5", &options).unwrap().format(&options), "5");
+        assert!(evaluate("i∠90", &options).is_err());
+    }
+
+    #[test]
+    fn quantities_with_units_are_checked_and_converted() {
+        let options = Options::default();
+        let cases = [
+            ("5 km + 300 m", "5.3 km"),
+            ("300 m + 5 km", "5300 m"),
+            ("9.81 m/s^2 * 70 kg", "686.7 N"),
+            ("60 mph to km/h", "96.56064 km/h"),
+            ("60 mph in m/s", "26.8224 m/s"),
+            ("100 km / 2 h", "50 km/h"),
+            ("(3 m)^2", "9 m^2"),
+            ("2 m * 3 m * 4 m to L", "24000 L"),
+            ("12 V / 4 ohm", "3 A"),
+            ("1 / 2 s", "0.5 Hz"),
+            ("-(5 km) * 2", "-10 km"),
+            ("1 kWh to J", "3600000 J"),
+            ("x = 3 km; x to m", "3000 m"),
+            // A conversion keeps the unit as written, while arithmetic on
+            // the result simplifies it again
+            ("1 N to kg*m/s^2", "1 kg*m/s^2"),
+            ("1 kWh to W*h", "1000 W*h"),
+            ("(1 N to kg*m/s^2) * 2", "2 N"),
+            ("2 hour + 30 minute", "2.5 hour"),
+            ("1 mile to ft", "5280 ft"),
+            ("12 inches to cm", "30.48 cm"),
+            ("15 degC to K", "15 K"),
+        ];
+        for (input, output) in cases {
+            assert_eq!(evaluate(input, &options).unwrap().to_string(), output);
+        }
+        // Quantities without a dimension are plain numbers
+        assert_eq!(
+            evaluate("1 km / 1 m", &options).unwrap(),
+            Number::from(1000)
+        );
+        assert_eq!(
+            evaluate("5 km > 300 m", &options).unwrap(),
+            Value::Boolean(true)
+        );
+        assert_eq!(
+            evaluate("100 cm == 1 m", &options).unwrap(),
+            Value::Boolean(true)
+        );
+        assert!(matches!(
+            evaluate("5 m + 3 s", &options),
+            Err(Error::DimensionMismatch(Operator::Add, _, _))
+        ));
+        assert!(matches!(
+            evaluate("5 km to s", &options),
+            Err(Error::DimensionMismatch(Operator::Convert, _, _))
+        ));
+        assert!(matches!(
+            evaluate("5 m < 3", &options),
+            Err(Error::DimensionMismatch(Operator::Less, _, _))
+        ));
+        assert!(evaluate("5 to km", &options).is_err());
+        assert!(evaluate("(2 m)^0.5", &options).is_err());
+        assert!(evaluate("5 km mod 2", &options).is_err());
+        assert!(evaluate("sqrt(4 m)", &options).is_err());
+        // A variable shadows the unit with the same name
+        assert!(evaluate("m = 2; 3 m", &options).is_err());
+        assert_eq!(evaluate("m = 2; 3 * m", &options).unwrap(), Number::from(6));
+
+        let options = Options {
+            mode: NumericMode::Rational,
+            ..Options::default()
+        };
+        let result = evaluate("60 mph to km/h", &options).unwrap();
+        assert_eq!(result.format(&options), "301752/3125 km/h");
     }
 
-    fn subtract(a: isize, b: isize) -> isize {
-        a - b
+    #[test]
+    fn currency_amounts_convert_with_exchange_rates_and_round_to_even() {
+        let rates = Rates::parse(
+            "# Units of each currency per US dollar\n\
+             date = \"2024-05-01\"\n\
+             \n\
+             [rates]\n\
+             USD = 1\n\
+             EUR = 0.92\n\
+             GBP = 0.79\n\
+             JPY = 151.5\n",
+        )
+        .unwrap();
+        let options = Options {
+            rates: Some(Rc::new(rates)),
+            ..Options::default()
+        };
+        let cases = [
+            (
+                "100 USD + 50 EUR in GBP",
+                "121.93 GBP (rates of 2024-05-01)",
+            ),
+            ("100 USD to JPY", "15150 JPY (rates of 2024-05-01)"),
+            ("0.1 USD + 0.2 USD", "0.30 USD (rates of 2024-05-01)"),
+            ("19.99 USD * 3", "59.97 USD (rates of 2024-05-01)"),
+            ("0.125 USD", "0.12 USD (rates of 2024-05-01)"),
+            ("0.135 USD", "0.14 USD (rates of 2024-05-01)"),
+            ("2.5 JPY", "2 JPY (rates of 2024-05-01)"),
+            ("3.5 JPY", "4 JPY (rates of 2024-05-01)"),
+            ("5 EUR / 1 kg * 3 kg", "15.00 EUR (rates of 2024-05-01)"),
+            ("10 USD / 2 h", "5 USD/h (rates of 2024-05-01)"),
+        ];
+        for (input, output) in cases {
+            assert_eq!(evaluate(input, &options).unwrap().format(&options), output);
+        }
+        assert_eq!(
+            evaluate("100 USD > 90 EUR", &options).unwrap(),
+            Value::Boolean(true)
+        );
+        assert!(matches!(
+            evaluate("5 USD + 3 m", &options),
+            Err(Error::DimensionMismatch(Operator::Add, _, _))
+        ));
+        // Currencies are unknown without exchange rates
+        assert!(evaluate("5 USD", &Options::default()).is_err());
     }
 
-    fn multiply(a: isize, b: isize) -> isize {
-        a * b
+    #[test]
+    fn exchange_rates_fail_to_parse_with_missing_or_invalid_entries() {
+        assert!(Rates::parse("[rates]\nUSD = 1").is_err());
+        assert!(Rates::parse("date = 2024-05-01").is_err());
+        assert!(Rates::parse("date = 2024-05-01\n[rates]\nUSD = x").is_err());
+        assert!(Rates::parse("date = 2024-05-01\n[rates]\nUSD = 0").is_err());
+        assert!(Rates::parse("date = 2024-05-01\n[rates]\nUSD 1").is_err());
+        assert!(Rates::parse("date = 2024-05-01\nbase = USD\n[rates]\nUSD = 1").is_err());
+        let rates = Rates::parse("date = 2024-05-01\n[rates]\nUSD = 1 # base").unwrap();
+        assert_eq!(rates.date, "2024-05-01");
     }
 
-    fn divide(a: isize, b: isize) -> Result<isize> {
-        if b == 0 {
-            return Err(Error::DivideByZero(a, b));
+    #[test]
+    fn modular_mode_reduces_arithmetic_and_inverts_divisors() {
+        let options = Options {
+            modulus: Some(BigInt::from(17)),
+            ..Options::default()
+        };
+        let cases = [
+            ("20", 3),
+            ("-3", 14),
+            ("5 - 9", 13),
+            ("3 / 4", 5),
+            ("2^-1", 9),
+            ("2^20", 16),
+            ("3 * 6", 1),
+            ("7 // 2", 3),
+            ("f(x) = x^2 + 1; f(5)", 9),
+            ("powmod(4, 13, 497)", 3),
+        ];
+        for (input, output) in cases {
+            assert_eq!(evaluate(input, &options).unwrap(), Number::from(output));
+        }
+        assert_eq!(
+            evaluate("3 * 6 == 1", &options).unwrap(),
+            Value::Boolean(true)
+        );
+        assert!(matches!(
+            evaluate("0.5 + 1", &options),
+            Err(Error::InvalidOperand(Operator::Add, _))
+        ));
+        // Integers are residues on entry, except for exponents and shifts
+        for (input, output) in [("18 mod 5", 1), ("2^18", 4), ("1 << 18", 4), ("-1 & 3", 0)] {
+            assert_eq!(evaluate(input, &options).unwrap(), Number::from(output));
+        }
+        for input in ["18 == 1", "18 + 0 == 1", "18 < 2"] {
+            assert_eq!(evaluate(input, &options).unwrap(), Value::Boolean(true));
         }
-        Ok(a / b)
+        for input in ["1.5", "sqrt(18)", "sqrt(-1)"] {
+            assert!(matches!(
+                evaluate(input, &options),
+                Err(Error::NoResidue(_, _))
+            ));
+        }
+        let options = Options {
+            modulus: Some(BigInt::from(8)),
+            ..Options::default()
+        };
+        assert_eq!(
+            evaluate("3 / 4", &options).unwrap_err().to_string(),
+            "4 has no inverse modulo 8"
+        );
+        assert!(matches!(
+            evaluate("2^-1", &options),
+            Err(Error::NoInverse(_, _))
+        ));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::types::*;
-    use super::Calculator;
+    #[test]
+    fn modular_functions_solve_powers_inverses_and_congruences() {
+        let options = Options::default();
+        let cases = [
+            ("powmod(4, 13, 497)", 445),
+            ("powmod(3, -1, 7)", 5),
+            ("modinv(3, 11)", 4),
+            ("modinv(-3, 11)", 7),
+            ("crt(2, 3, 3, 5, 2, 7)", 23),
+            ("crt(5, 12)", 5),
+            ("crt(1, 4, 3, 6)", 9),
+            ("crt(1, 2, 1, 4)", 1),
+            ("crt(3, 4, 1, 6, 2, 5)", 7),
+        ];
+        for (input, output) in cases {
+            assert_eq!(evaluate(input, &options).unwrap(), Number::from(output));
+        }
+        assert!(matches!(
+            evaluate("modinv(4, 8)", &options),
+            Err(Error::NoInverse(_, _))
+        ));
+        assert_eq!(
+            evaluate("crt(1, 4, 2, 6)", &options)
+                .unwrap_err()
+                .to_string(),
+            "no integer is 1 modulo 4 and 2 modulo 6 at the same time"
+        );
+        for input in ["crt(1, 2, 3)", "powmod(2, 10, 0)", "modinv(2.5, 7)"] {
+            assert!(matches!(
+                evaluate(input, &options),
+                Err(Error::InvalidArgument(_, _))
+            ));
+        }
+    }
 
     #[test]
-    fn addition_behaves_correctly() {
-        assert_eq!(Calculator::add(1, 2), 3);
+    fn data_sizes_and_rates_scale_to_readable_prefixes() {
+        let options = Options::default();
+        let cases = [
+            ("1.5 TiB / 100 MB/s", "4.581298449066667 h"),
+            ("1 MB / 100 kB/s", "10 s"),
+            ("6 GB / 100 MB/s", "1 min"),
+            ("1 GB / (100 Mbit/h)", "80 h"),
+            ("1500 MB", "1.5 GB"),
+            ("2 GiB + 512 MiB", "2.5 GiB"),
+            ("0.5 GB", "500 MB"),
+            ("100 Mbit/s * 1 h", "360 Gbit"),
+            ("2048 KiB/s * 1", "2 MiB/s"),
+            ("2 GB to MB", "2000 MB"),
+            ("1 GB to MiB", "953.67431640625 MiB"),
+            ("1 MB/s to Mbit/s", "8 Mbit/s"),
+            ("8 bit to B", "1 B"),
+            ("3 TB * 0", "0 TB"),
+        ];
+        for (input, output) in cases {
+            assert_eq!(evaluate(input, &options).unwrap().to_string(), output);
+        }
+        assert_eq!(
+            evaluate("1 KiB / 1 kB", &options).unwrap(),
+            Value::Number(Number::Float(1.024))
+        );
+        // A rate is only grouped when nothing separates its units
+        assert_eq!(
+            evaluate("1 GB / 1 MB / s", &options).unwrap().to_string(),
+            "1000 Hz"
+        );
+        assert!(matches!(
+            evaluate("1 GB + 1 s", &options),
+            Err(Error::DimensionMismatch(Operator::Add, _, _))
+        ));
     }
 
     #[test]
-    fn subtraction_behaves_correctly() {
-        assert_eq!(Calculator::subtract(3, 2), 1);
+    fn dates_times_and_durations_combine_with_arithmetic() {
+        let options = Options::default();
+        let cases = [
+            ("2026-12-25 - 2026-10-18", "68 days"),
+            ("2026-10-18 + 90 days", "2027-01-16"),
+            ("2024-03-01 - 1 day", "2024-02-29"),
+            ("2026-10-18 + 3h 20m", "2026-10-18T03:20"),
+            ("2026-10-18T23:30 + 45 min", "2026-10-19T00:15"),
+            ("2026-10-18T09:05:07 - 2026-10-18", "9.085277777777778 h"),
+            ("14:30 to h", "14.5 h"),
+            ("3h 20m", "200 min"),
+            ("1h30m15s", "5415 s"),
+            ("1w 2d", "9 days"),
+            ("20m", "20 m"),
+        ];
+        for (input, output) in cases {
+            assert_eq!(evaluate(input, &options).unwrap().to_string(), output);
+        }
+        assert_eq!(
+            evaluate("2026-10-18 < 2026-10-18T00:01", &options).unwrap(),
+            Value::Boolean(true)
+        );
+        assert_eq!(
+            evaluate("today - today", &options).unwrap().to_string(),
+            "0 days"
+        );
+        assert!(matches!(
+            evaluate("2026-10-18 + 5", &options),
+            Err(Error::InvalidOperand(Operator::Add, _))
+        ));
+        assert!(matches!(
+            evaluate("2026-10-18 + 5 m", &options),
+            Err(Error::InvalidOperand(Operator::Add, _))
+        ));
+        assert!(matches!(
+            evaluate("2026-10-18 + 2026-10-18", &options),
+            Err(Error::InvalidOperand(Operator::Add, _))
+        ));
+        // The : of an open conditional expression is never part of a time
+        assert_eq!(evaluate("true?1:20", &options).unwrap(), Number::from(1));
+        assert_eq!(evaluate("false?1:20", &options).unwrap(), Number::from(20));
+        assert!(matches!(
+            evaluate("true ? 10:20 : 5", &options),
+            Err(Error::InvalidExpression(_))
+        ));
+        assert_eq!(
+            evaluate("true ? (10:20) : 5 min", &options)
+                .unwrap()
+                .to_string(),
+            "620 min"
+        );
+        for input in ["2026-02-29", "2026-13-01", "2026-10-18T24:00", "9:60"] {
+            assert!(matches!(
+                evaluate(input, &options),
+                Err(Error::InvalidExpression(_))
+            ));
+        }
     }
 
     #[test]
-    fn multiplication_behaves_correctly() {
-        assert_eq!(Calculator::multiply(2, 3), 6);
+    fn calendar_functions_follow_iso_weeks_and_business_days() {
+        let options = Options::default();
+        let cases = [
+            ("weekday(2026-10-18)", "7"),
+            ("weekday(2026-10-19T08:00)", "1"),
+            ("week(2026-10-18)", "42"),
+            ("week(2021-01-03)", "53"),
+            ("week(2024-12-30)", "1"),
+            ("workday(2026-10-16, 1)", "2026-10-19"),
+            ("workday(2026-10-17, 1)", "2026-10-19"),
+            ("workday(2026-10-18, -1)", "2026-10-16"),
+            ("workday(2026-10-19, 10)", "2026-11-02"),
+            ("networkdays(2026-10-19, 2026-10-30)", "10"),
+            ("networkdays(2026-10-30, 2026-10-19)", "-10"),
+            ("networkdays(2026-10-17, 2026-10-18)", "0"),
+        ];
+        for (input, output) in cases {
+            assert_eq!(evaluate(input, &options).unwrap().to_string(), output);
+        }
+        assert!(matches!(
+            evaluate("weekday(5)", &options),
+            Err(Error::InvalidArgument(_, _))
+        ));
+        assert!(matches!(
+            evaluate("workday(2026-10-18, 1.5)", &options),
+            Err(Error::InvalidArgument(_, _))
+        ));
+        assert_eq!("+05:30".parse::<UtcOffset>().unwrap().seconds(), 19_800);
+        assert_eq!("-3".parse::<UtcOffset>().unwrap().to_string(), "-03:00");
+        assert!("+15".parse::<UtcOffset>().is_err());
     }
 
     #[test]
-    fn division_behaves_correctly() {
-        assert_eq!(Calculator::divide(6, 3).unwrap(), 2);
+    fn tokenization_recognizes_imaginary_literals() {
+        assert_eq!(
+            Calculator::tokenize_expression("2i + 0.5i * i - 3it").unwrap(),
+            vec![
+                Token::Imaginary(2.into()),
+                Token::Operator(Operator::Add),
+                Token::Imaginary(Number::Rational(Rational::new(1.into(), 2.into()))),
+                Token::Operator(Operator::Multiply),
+                Token::Identifier("i".into(), 12),
+                Token::Operator(Operator::Subtract),
+                Token::Operand(3.into()),
+                Token::Identifier("it".into(), 17),
+            ]
+        );
     }
 
     #[test]
-    fn division_behaves_correctly_with_zero_divisor() {
-        assert!(Calculator::divide(1, 0).is_err());
+    fn tokenization_distinguishes_comparisons_from_other_operators() {
+        assert_eq!(
+            Calculator::tokenize_expression("x == 1 != y = 2 <= 3 << 4 >= 5 >> 6 < 7 > 8").unwrap(),
+            vec![
+                Token::Identifier("x".into(), 0),
+                Token::Operator(Operator::Equal),
+                Token::Operand(1.into()),
+                Token::Operator(Operator::NotEqual),
+                Token::Identifier("y".into(), 10),
+                Token::Assign,
+                Token::Operand(2.into()),
+                Token::Operator(Operator::LessEqual),
+                Token::Operand(3.into()),
+                Token::Operator(Operator::ShiftLeft),
+                Token::Operand(4.into()),
+                Token::Operator(Operator::GreaterEqual),
+                Token::Operand(5.into()),
+                Token::Operator(Operator::ShiftRight),
+                Token::Operand(6.into()),
+                Token::Operator(Operator::Less),
+                Token::Operand(7.into()),
+                Token::Operator(Operator::Greater),
+                Token::Operand(8.into()),
+            ]
+        );
+        assert_eq!(
+            Calculator::tokenize_expression("5! != true ? 1 : 2").unwrap(),
+            vec![
+                Token::Operand(5.into()),
+                Token::Operator(Operator::Factorial),
+                Token::Operator(Operator::NotEqual),
+                Token::Boolean(true),
+                Token::Operator(Operator::Conditional),
+                Token::Operand(1.into()),
+                Token::Operator(Operator::ConditionalElse),
+                Token::Operand(2.into()),
+            ]
+        );
     }
 
     #[test]
-    fn evaluation_behaves_correctly() {
-        let i1 = "3 + 4 * 2";
-        let o1 = 11;
+    fn left_shifts_respect_overflow_policy() {
+        let mut options = Options {
+            overflow: OverflowPolicy::Wrapping,
+            ..Options::default()
+        };
+        assert_eq!(
+            evaluate("1 << 63", &options).unwrap(),
+            Number::Integer(i64::MIN.into())
+        );
+        assert_eq!(
+            evaluate("3 << 100000000", &options).unwrap(),
+            Number::from(0)
+        );
 
-        let i2 = "3 + 4 / 2";
-        let o2 = 5;
+        options.overflow = OverflowPolicy::Checked;
+        assert!(evaluate("1 << 63", &options).is_err());
+    }
 
-        assert_eq!(Calculator::evaluate(i1).unwrap(), o1);
-        assert_eq!(Calculator::evaluate(i2).unwrap(), o2);
+    #[test]
+    fn integer_results_are_formatted_in_the_output_radix() {
+        let mut options = Options::default();
+        let result = evaluate("0xff + 0b1", &options).unwrap();
+
+        options.radix = 16;
+        assert_eq!(result.format(&options), "0x100");
+        options.radix = 2;
+        assert_eq!(result.format(&options), "0b100000000");
+        options.radix = 8;
+        assert_eq!(result.format(&options), "0o400");
+        options.radix = 36;
+        assert_eq!(result.format(&options), "74");
+        options.radix = 16;
+        assert_eq!(Number::from(-255).format(&options), "-0xff");
+        assert_eq!(Number::Float(0.5).format(&options), "0.5");
+        // Only plain integers are formatted in the output radix
+        for (input, output) in [("5 km", "5 km"), ("3+4i", "3+4i"), ("0x10 * i", "16i")] {
+            assert_eq!(evaluate(input, &options).unwrap().format(&options), output);
+        }
     }
 
     #[test]
     fn tokenization_behaves_correctly() {
         let i1 = "3 + 4 * 2";
         let e1 = vec![
-            Token::Operand(3),
+            Token::Operand(3.into()),
             Token::Operator(Operator::Add),
-            Token::Operand(4),
+            Token::Operand(4.into()),
             Token::Operator(Operator::Multiply),
-            Token::Operand(2),
+            Token::Operand(2.into()),
         ];
 
         let i2 = "3 + 4 / 2";
         let e2 = vec![
-            Token::Operand(3),
+            Token::Operand(3.into()),
             Token::Operator(Operator::Add),
-            Token::Operand(4),
+            Token::Operand(4.into()),
             Token::Operator(Operator::Divide),
-            Token::Operand(2),
+            Token::Operand(2.into()),
         ];
 
         assert_eq!(Calculator::tokenize_expression(i1).unwrap(), e1);
@@ -299,21 +3413,73 @@ mod tests {
     fn tokenization_handles_digit_termination_correctly() {
         let i1 = "3+4*2";
         let e1 = vec![
-            Token::Operand(3),
+            Token::Operand(3.into()),
             Token::Operator(Operator::Add),
-            Token::Operand(4),
+            Token::Operand(4.into()),
             Token::Operator(Operator::Multiply),
-            Token::Operand(2),
+            Token::Operand(2.into()),
+        ];
+        assert_eq!(Calculator::tokenize_expression(i1).unwrap(), e1);
+    }
+
+    #[test]
+    fn tokenization_handles_function_calls() {
+        let i1 = "max(1, -2)";
+        let e1 = vec![
+            Token::Function("max".into(), 0),
+            Token::LeftParen,
+            Token::Operand(1.into()),
+            Token::Comma,
+            Token::Operator(Operator::Negate),
+            Token::Operand(2.into()),
+            Token::RightParen,
         ];
         assert_eq!(Calculator::tokenize_expression(i1).unwrap(), e1);
     }
 
     #[test]
     fn tokenization_fails_on_unrecognized_character() {
-        let i1 = "3+4!2";
+        let i1 = "3+4@2";
         assert!(Calculator::tokenize_expression(i1).is_err());
     }
 
+    #[test]
+    fn tokenization_fails_on_malformed_decimals() {
+        assert!(Calculator::tokenize_expression("1.2.3").is_err());
+        assert!(Calculator::tokenize_expression("3 + .").is_err());
+    }
+
+    #[test]
+    fn tokenization_distinguishes_unary_from_binary_signs() {
+        let i1 = "-3 - (-4)";
+        let e1 = vec![
+            Token::Operator(Operator::Negate),
+            Token::Operand(3.into()),
+            Token::Operator(Operator::Subtract),
+            Token::LeftParen,
+            Token::Operator(Operator::Negate),
+            Token::Operand(4.into()),
+            Token::RightParen,
+        ];
+        assert_eq!(Calculator::tokenize_expression(i1).unwrap(), e1);
+    }
+
+    #[test]
+    fn tokenization_handles_prefixed_literals() {
+        let i1 = "0xff + 0b1010_0101 - 0o17";
+        let e1 = vec![
+            Token::Operand(255.into()),
+            Token::Operator(Operator::Add),
+            Token::Operand(165.into()),
+            Token::Operator(Operator::Subtract),
+            Token::Operand(15.into()),
+        ];
+        assert_eq!(Calculator::tokenize_expression(i1).unwrap(), e1);
+        assert!(Calculator::tokenize_expression("0x").is_err());
+        assert!(Calculator::tokenize_expression("0b102").is_err());
+        assert!(Calculator::tokenize_expression("0xfg").is_err());
+    }
+
     #[test]
     fn validation_catches_zero_length_expression() {
         let i1 = vec![];
@@ -323,7 +3489,7 @@ mod tests {
     #[test]
     fn validation_catches_consecutive_operands() {
         // 2 2
-        let i1 = vec![Token::Operand(2), Token::Operand(2)];
+        let i1 = vec![Token::Operand(2.into()), Token::Operand(2.into())];
         assert!(Calculator::validate_expression(&i1).is_err());
     }
 
@@ -331,7 +3497,7 @@ mod tests {
     fn validation_catches_operor_followed_by_right_paren() {
         // 2 + )
         let i1 = vec![
-            Token::Operand(2),
+            Token::Operand(2.into()),
             Token::Operator(Operator::Add),
             Token::RightParen,
         ];
@@ -341,14 +3507,26 @@ mod tests {
     #[test]
     fn validation_catches_operator_without_trailing_operand() {
         // 2 +
-        let i1 = vec![Token::Operand(2), Token::Operator(Operator::Add)];
+        let i1 = vec![Token::Operand(2.into()), Token::Operator(Operator::Add)];
         assert!(Calculator::validate_expression(&i1).is_err());
     }
 
     #[test]
     fn validation_catches_operator_without_leading_operand() {
         // + 2
-        let i1 = vec![Token::Operator(Operator::Add), Token::Operand(2)];
+        let i1 = vec![Token::Operator(Operator::Add), Token::Operand(2.into())];
+        assert!(Calculator::validate_expression(&i1).is_err());
+    }
+
+    #[test]
+    fn validation_catches_binary_operator_after_left_paren() {
+        // ( * 2 )
+        let i1 = vec![
+            Token::LeftParen,
+            Token::Operator(Operator::Multiply),
+            Token::Operand(2.into()),
+            Token::RightParen,
+        ];
         assert!(Calculator::validate_expression(&i1).is_err());
     }
 
@@ -356,7 +3534,7 @@ mod tests {
     fn validation_catches_consecutive_operators() {
         // 2 + +
         let i1 = vec![
-            Token::Operand(2),
+            Token::Operand(2.into()),
             Token::Operator(Operator::Add),
             Token::Operator(Operator::Add),
         ];
@@ -367,18 +3545,18 @@ mod tests {
     fn infix_to_postfix_conversion_behaves_correctly() {
         // 3 + 4 * 2
         let e1 = vec![
-            Token::Operand(3),
+            Token::Operand(3.into()),
             Token::Operator(Operator::Add),
-            Token::Operand(4),
+            Token::Operand(4.into()),
             Token::Operator(Operator::Multiply),
-            Token::Operand(2),
+            Token::Operand(2.into()),
         ];
 
         // 3 4 2 * +
         let r1 = vec![
-            Token::Operand(3),
-            Token::Operand(4),
-            Token::Operand(2),
+            Token::Operand(3.into()),
+            Token::Operand(4.into()),
+            Token::Operand(2.into()),
             Token::Operator(Operator::Multiply),
             Token::Operator(Operator::Add),
         ];
@@ -386,20 +3564,20 @@ mod tests {
         // (3 + 4) * 2
         let e2 = vec![
             Token::LeftParen,
-            Token::Operand(3),
+            Token::Operand(3.into()),
             Token::Operator(Operator::Add),
-            Token::Operand(4),
+            Token::Operand(4.into()),
             Token::RightParen,
             Token::Operator(Operator::Multiply),
-            Token::Operand(2),
+            Token::Operand(2.into()),
         ];
 
         // 3 4 + 2 *
         let r2 = vec![
-            Token::Operand(3),
-            Token::Operand(4),
+            Token::Operand(3.into()),
+            Token::Operand(4.into()),
             Token::Operator(Operator::Add),
-            Token::Operand(2),
+            Token::Operand(2.into()),
             Token::Operator(Operator::Multiply),
         ];
 
@@ -413,21 +3591,80 @@ mod tests {
         );
     }
 
+    #[test]
+    fn infix_to_postfix_conversion_groups_same_precedence_operators_from_the_left() {
+        // 8 - 4 - 2
+        let e1 = vec![
+            Token::Operand(8.into()),
+            Token::Operator(Operator::Subtract),
+            Token::Operand(4.into()),
+            Token::Operator(Operator::Subtract),
+            Token::Operand(2.into()),
+        ];
+
+        // 8 4 - 2 -
+        let r1 = vec![
+            Token::Operand(8.into()),
+            Token::Operand(4.into()),
+            Token::Operator(Operator::Subtract),
+            Token::Operand(2.into()),
+            Token::Operator(Operator::Subtract),
+        ];
+
+        assert_eq!(
+            Calculator::marshal_infix_expression_to_postfix(e1).unwrap(),
+            r1
+        );
+    }
+
+    #[test]
+    fn infix_to_postfix_conversion_handles_function_calls() {
+        // max ( 1 , 2 + 3 ) * 4
+        let e1 = vec![
+            Token::Function("max".into(), 0),
+            Token::LeftParen,
+            Token::Operand(1.into()),
+            Token::Comma,
+            Token::Operand(2.into()),
+            Token::Operator(Operator::Add),
+            Token::Operand(3.into()),
+            Token::RightParen,
+            Token::Operator(Operator::Multiply),
+            Token::Operand(4.into()),
+        ];
+
+        // 1 2 3 + max 4 *
+        let r1 = vec![
+            Token::Operand(1.into()),
+            Token::Operand(2.into()),
+            Token::Operand(3.into()),
+            Token::Operator(Operator::Add),
+            Token::Call("max".into(), 2, 0),
+            Token::Operand(4.into()),
+            Token::Operator(Operator::Multiply),
+        ];
+
+        assert_eq!(
+            Calculator::marshal_infix_expression_to_postfix(e1).unwrap(),
+            r1
+        );
+    }
+
     #[test]
     fn infix_to_postfix_conversion_fails_on_mismatched_parentheses() {
         // ( 3 + 4
         let e1 = vec![
             Token::LeftParen,
-            Token::Operand(3),
+            Token::Operand(3.into()),
             Token::Operator(Operator::Add),
-            Token::Operand(4),
+            Token::Operand(4.into()),
         ];
 
         // 3 + 4 )
         let e2 = vec![
-            Token::Operand(3),
+            Token::Operand(3.into()),
             Token::Operator(Operator::Add),
-            Token::Operand(4),
+            Token::Operand(4.into()),
             Token::RightParen,
         ];
 
@@ -439,36 +3676,45 @@ mod tests {
     fn postfix_expression_evaluation_behaves_correctly() {
         // 3 4 2 * +
         let e1 = vec![
-            Token::Operand(3),
-            Token::Operand(4),
-            Token::Operand(2),
+            Token::Operand(3.into()),
+            Token::Operand(4.into()),
+            Token::Operand(2.into()),
             Token::Operator(Operator::Multiply),
             Token::Operator(Operator::Add),
         ];
 
         // 3 4 2 / +
         let e2 = vec![
-            Token::Operand(3),
-            Token::Operand(4),
-            Token::Operand(2),
+            Token::Operand(3.into()),
+            Token::Operand(4.into()),
+            Token::Operand(2.into()),
             Token::Operator(Operator::Divide),
             Token::Operator(Operator::Add),
         ];
 
-        assert_eq!(Calculator::evaluate_postfix_expression(e1).unwrap(), 11);
-        assert_eq!(Calculator::evaluate_postfix_expression(e2).unwrap(), 5);
+        assert_eq!(
+            Calculator::evaluate_postfix_expression(e1, &mut Context::default()).unwrap(),
+            Value::from(11)
+        );
+        assert_eq!(
+            Calculator::evaluate_postfix_expression(e2, &mut Context::default()).unwrap(),
+            Value::from(5)
+        );
     }
 
     #[test]
     fn postfix_expression_evaluation_handles_single_operand_input_correctly() {
-        let e1 = vec![Token::Operand(5)];
-        assert_eq!(Calculator::evaluate_postfix_expression(e1).unwrap(), 5);
+        let e1 = vec![Token::Operand(5.into())];
+        assert_eq!(
+            Calculator::evaluate_postfix_expression(e1, &mut Context::default()).unwrap(),
+            Value::from(5)
+        );
     }
 
     #[test]
     #[should_panic]
     fn postfix_expression_evaluation_panics_on_zero_length_input() {
         let e1 = vec![];
-        Calculator::evaluate_postfix_expression(e1).unwrap();
+        Calculator::evaluate_postfix_expression(e1, &mut Context::default()).unwrap();
     }
 }
@@ -0,0 +1,106 @@
+use super::{
+    bigint::BigInt,
+    number::Number,
+    types::{Error, Operator, Result},
+    Calculator,
+};
+
+/// Returns whether an operator is carried out modulo the modulus in modular
+/// mode. Other operators work on residues as they do on any integers, and
+/// their results are reduced when they are used.
+pub fn supports(operator: Operator) -> bool {
+    matches!(
+        operator,
+        Operator::Add
+            | Operator::Subtract
+            | Operator::Multiply
+            | Operator::ImplicitMultiply
+            | Operator::Of
+            | Operator::Divide
+            | Operator::Power
+    )
+}
+
+/// Reduces an integer to its least non-negative residue, so 20 is 3 modulo
+/// 17. Other numbers are returned unchanged.
+pub fn reduce(number: Number, modulus: &BigInt) -> Number {
+    match number {
+        Number::Integer(integer) => Number::Integer(integer.rem_euclid(modulus)),
+        number => number,
+    }
+}
+
+/// Applies an operator to two integers modulo the modulus. Division
+/// multiplies by the inverse of the divisor, and so does a power with a
+/// negative exponent, which both fail when there is no inverse. Exponents
+/// are not residues, since `a^(e + N)` is not `a^e` modulo `N` in general,
+/// so they are the only operands that are not reduced first.
+pub fn apply_binary_operator(
+    operator: Operator,
+    a: Number,
+    b: Number,
+    modulus: &BigInt,
+) -> Result<Number> {
+    let a = Calculator::integer_operand(operator, a)?.rem_euclid(modulus);
+    let b = match operator {
+        Operator::Power => Calculator::integer_operand(operator, b)?,
+        _ => Calculator::integer_operand(operator, b)?.rem_euclid(modulus),
+    };
+    let result = match operator {
+        Operator::Add => &a + &b,
+        Operator::Subtract => &a - &b,
+        Operator::Multiply | Operator::ImplicitMultiply | Operator::Of => &a * &b,
+        Operator::Divide => &a * &inverse(&b, modulus)?,
+        Operator::Power => power(&a, &b, modulus)?,
+        _ => unreachable!(), // Only called for operators that support modular arithmetic
+    };
+    Ok(Number::Integer(result.rem_euclid(modulus)))
+}
+
+/// Raises an integer to a power modulo the modulus. A negative exponent
+/// raises the inverse of the integer instead.
+pub fn power(base: &BigInt, exponent: &BigInt, modulus: &BigInt) -> Result<BigInt> {
+    if exponent.is_negative() {
+        Ok(inverse(base, modulus)?.pow_mod(&-exponent.clone(), modulus))
+    } else {
+        Ok(base.pow_mod(exponent, modulus))
+    }
+}
+
+/// Returns the inverse of an integer modulo the modulus, or fails if they
+/// share a factor, in which case there is none.
+pub fn inverse(a: &BigInt, modulus: &BigInt) -> Result<BigInt> {
+    a.mod_inverse(modulus)
+        .ok_or_else(|| Error::NoInverse(a.clone(), modulus.clone()))
+}
+
+/// Solves a system of congruences, each given as a residue and a modulus,
+/// with the Chinese remainder theorem. Returns the least non-negative
+/// solution, which is unique modulo the least common multiple of the moduli.
+/// Moduli that share a factor are fine as long as the congruences agree on
+/// it, so `crt(1, 4, 3, 6)` is 9, and otherwise there is no solution.
+pub fn chinese_remainder(congruences: &[(BigInt, BigInt)]) -> Result<BigInt> {
+    let mut solution = BigInt::from(0);
+    let mut multiple = BigInt::from(1);
+    for (residue, modulus) in congruences {
+        // Adds the multiple of the moduli so far that also satisfies the
+        // next congruence, which leaves the earlier ones satisfied. Such a
+        // multiple exists when the difference is divisible by the factor
+        // that the moduli share.
+        let shared = multiple.gcd(modulus);
+        let (difference, remainder) = (residue - &solution).div_rem(&shared);
+        if !remainder.is_zero() {
+            return Err(Error::ConflictingCongruences(vec![
+                (solution, multiple),
+                (residue.rem_euclid(modulus), modulus.clone()),
+            ]));
+        }
+        let modulus = modulus.div_rem(&shared).0;
+        // Guaranteed to exist because the shared factor was divided out
+        let step = multiple.div_rem(&shared).0.mod_inverse(&modulus).unwrap();
+        let count = (&difference * &step).rem_euclid(&modulus);
+        solution = &solution + &(&count * &multiple);
+        multiple = &multiple * &modulus;
+    }
+    Ok(solution.rem_euclid(&multiple))
+}
@@ -0,0 +1,140 @@
+use std::{cmp::Ordering, fmt::Display};
+
+use super::{
+    bigint::BigInt,
+    options::{Options, RationalFormat},
+    rational::Rational,
+};
+
+/// A numeric value produced during evaluation. Integers are exact and are
+/// preferred whenever a result can be represented exactly. Rationals hold
+/// exact fractions, and floats hold approximations of everything else.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Number {
+    Integer(BigInt),
+    Rational(Rational),
+    Float(f64),
+}
+
+impl Number {
+    pub fn is_zero(&self) -> bool {
+        match self {
+            Number::Integer(integer) => integer.is_zero(),
+            Number::Rational(rational) => rational.is_zero(),
+            Number::Float(float) => *float == 0.0,
+        }
+    }
+
+    /// Returns whether this number holds an exact value.
+    pub fn is_exact(&self) -> bool {
+        !matches!(self, Number::Float(_))
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            Number::Integer(integer) => integer.to_f64(),
+            Number::Rational(rational) => rational.to_f64(),
+            Number::Float(float) => *float,
+        }
+    }
+
+    /// Converts an exact number to a rational. Panics if the number is a float.
+    pub fn to_rational(&self) -> Rational {
+        match self {
+            Number::Integer(integer) => Rational::from(integer.clone()),
+            Number::Rational(rational) => rational.clone(),
+            Number::Float(_) => panic!("Cannot convert a float to an exact rational."),
+        }
+    }
+
+    /// Formats this number for display using the formatting settings from the
+    /// options. Integers are formatted in the output radix, with a prefix for
+    /// hexadecimal, octal, and binary, while other numbers are always
+    /// formatted in decimal.
+    pub fn format(&self, options: &Options) -> String {
+        match self {
+            Number::Integer(integer) if options.radix != 10 => {
+                let prefix = match options.radix {
+                    16 => "0x",
+                    8 => "0o",
+                    2 => "0b",
+                    _ => "",
+                };
+                let digits = integer.abs().to_str_radix(options.radix);
+                let sign = if integer.is_negative() { "-" } else { "" };
+                format!("{}{}{}", sign, prefix, digits)
+            }
+            Number::Rational(rational) => match options.rational_format {
+                RationalFormat::Fraction => rational.to_string(),
+                RationalFormat::Mixed => rational.to_mixed_string(),
+                RationalFormat::Decimal => rational.to_decimal_string(options.digits),
+            },
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl PartialOrd for Number {
+    /// Compares exact numbers exactly. Comparisons that involve a float are
+    /// carried out on floats and have no ordering if either one is not a number.
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Number::Integer(a), Number::Integer(b)) => Some(a.cmp(b)),
+            (a, b) if a.is_exact() && b.is_exact() => Some(a.to_rational().cmp(&b.to_rational())),
+            (a, b) => a.to_f64().partial_cmp(&b.to_f64()),
+        }
+    }
+}
+
+impl From<BigInt> for Number {
+    fn from(value: BigInt) -> Self {
+        Number::Integer(value)
+    }
+}
+
+impl From<Rational> for Number {
+    /// Converts a rational to a number, collapsing whole values to integers.
+    fn from(value: Rational) -> Self {
+        if value.is_integer() {
+            Number::Integer(value.numerator().clone())
+        } else {
+            Number::Rational(value)
+        }
+    }
+}
+
+impl From<i32> for Number {
+    fn from(value: i32) -> Self {
+        Number::Integer(value.into())
+    }
+}
+
+impl From<f64> for Number {
+    fn from(value: f64) -> Self {
+        Number::Float(value)
+    }
+}
+
+impl Display for Number {
+    /// Integers are always printed in full and rationals are printed as
+    /// fractions. Floats are printed in plain decimal notation unless they are
+    /// very large or very small, in which case scientific notation keeps the
+    /// output readable. The tokenizer reads scientific notation back as a
+    /// float, so printed results can be used as input again.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Number::Integer(integer) => write!(f, "{}", integer),
+            Number::Rational(rational) => write!(f, "{}", rational),
+            Number::Float(float) => {
+                let magnitude = float.abs();
+                if magnitude.is_finite()
+                    && (magnitude >= 1e16 || (0.0 < magnitude && magnitude < 1e-6))
+                {
+                    write!(f, "{:e}", float)
+                } else {
+                    write!(f, "{}", float)
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,327 @@
+use std::{
+    f64::consts::{PI, TAU},
+    fmt::Display,
+    rc::Rc,
+    str::FromStr,
+};
+
+use super::{bigint::BigInt, currency::Rates};
+
+/// Determines what happens when an integer result does not fit in a
+/// signed integer of `BOUNDED_INTEGER_BITS` bits. The default policy
+/// places no bound on integers at all.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Integers grow as large as needed, so nothing ever overflows.
+    #[default]
+    Unbounded,
+    /// Results outside the bounded range fail with an overflow error.
+    Checked,
+    /// Results outside the bounded range wrap around in two's complement.
+    Wrapping,
+    /// Results outside the bounded range are clamped to the nearest bound.
+    Saturating,
+}
+
+/// The width of the signed integers used by the bounded overflow policies.
+pub const BOUNDED_INTEGER_BITS: usize = 64;
+
+/// The width of the largest integer that evaluation will produce, even with
+/// no bound in place. Operations such as exponentiation can otherwise produce
+/// results that take too long to compute or print.
+pub const MAX_INTEGER_BITS: usize = 1 << 18;
+
+/// The largest number of digits after the decimal point that results and
+/// constants can be computed to. Rational constants such as pi take time
+/// that grows with the square of the number of digits.
+pub const MAX_DIGITS: usize = 10_000;
+
+impl FromStr for OverflowPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "unbounded" => Ok(OverflowPolicy::Unbounded),
+            "checked" => Ok(OverflowPolicy::Checked),
+            "wrapping" => Ok(OverflowPolicy::Wrapping),
+            "saturating" => Ok(OverflowPolicy::Saturating),
+            _ => Err(format!(
+                "unrecognized overflow policy {}; expected one of unbounded, checked, wrapping, saturating",
+                s
+            )),
+        }
+    }
+}
+
+/// Determines how results that cannot be represented as integers, such as
+/// the quotient of `7 / 2`, are represented.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NumericMode {
+    /// Inexact results are approximated with double-precision floats.
+    #[default]
+    Float,
+    /// Inexact results are kept as exact fractions in lowest terms.
+    Rational,
+}
+
+impl FromStr for NumericMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "float" => Ok(NumericMode::Float),
+            "rational" => Ok(NumericMode::Rational),
+            _ => Err(format!(
+                "unrecognized numeric mode {}; expected one of float, rational",
+                s
+            )),
+        }
+    }
+}
+
+/// Determines how rational results are displayed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RationalFormat {
+    /// An improper fraction, such as `3/2`.
+    #[default]
+    Fraction,
+    /// A whole number followed by a proper fraction, such as `1 1/2`.
+    Mixed,
+    /// A decimal with a fixed number of digits after the point, such as `1.50`.
+    Decimal,
+}
+
+impl FromStr for RationalFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "fraction" => Ok(RationalFormat::Fraction),
+            "mixed" => Ok(RationalFormat::Mixed),
+            "decimal" => Ok(RationalFormat::Decimal),
+            _ => Err(format!(
+                "unrecognized rational format {}; expected one of fraction, mixed, decimal",
+                s
+            )),
+        }
+    }
+}
+
+/// Determines how complex results are displayed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ComplexFormat {
+    /// A real part and an imaginary part, such as `1+2i`.
+    #[default]
+    Rectangular,
+    /// A distance from zero and an angle in the unit set by the angle mode,
+    /// such as `2∠90`.
+    Polar,
+}
+
+impl FromStr for ComplexFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "rectangular" => Ok(ComplexFormat::Rectangular),
+            "polar" => Ok(ComplexFormat::Polar),
+            _ => Err(format!(
+                "unrecognized complex format {}; expected one of rectangular, polar",
+                s
+            )),
+        }
+    }
+}
+
+/// Determines the unit of the angles taken by trigonometric functions, such
+/// as `sin`, and returned by their inverses, such as `asin`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AngleMode {
+    /// A full turn is 2π radians.
+    #[default]
+    Radians,
+    /// A full turn is 360 degrees.
+    Degrees,
+    /// A full turn is 400 gradians.
+    Gradians,
+}
+
+impl AngleMode {
+    /// Returns the size of a full turn in this unit.
+    pub fn full_turn(&self) -> f64 {
+        match self {
+            AngleMode::Radians => TAU,
+            AngleMode::Degrees => 360.0,
+            AngleMode::Gradians => 400.0,
+        }
+    }
+
+    /// Converts an angle in this unit to radians. Angles in degrees and
+    /// gradians are first reduced to within a full turn, which is exact and
+    /// keeps large angles accurate.
+    pub fn angle_to_radians(&self, angle: f64) -> f64 {
+        match self {
+            AngleMode::Radians => angle,
+            _ => angle.rem_euclid(self.full_turn()) / self.full_turn() * TAU,
+        }
+    }
+
+    /// Converts an angle in radians to this unit.
+    pub fn angle_from_radians(&self, angle: f64) -> f64 {
+        match self {
+            AngleMode::Radians => angle,
+            _ => angle / PI * (self.full_turn() / 2.0),
+        }
+    }
+}
+
+impl FromStr for AngleMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "radians" | "rad" => Ok(AngleMode::Radians),
+            "degrees" | "deg" => Ok(AngleMode::Degrees),
+            "gradians" | "grad" => Ok(AngleMode::Gradians),
+            _ => Err(format!(
+                "unrecognized angle mode {}; expected one of radians, degrees, gradians",
+                s
+            )),
+        }
+    }
+}
+
+impl Display for AngleMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let abbreviation = match self {
+            AngleMode::Radians => "rad",
+            AngleMode::Degrees => "deg",
+            AngleMode::Gradians => "grad",
+        };
+        write!(f, "{}", abbreviation)
+    }
+}
+
+/// A fixed offset from UTC, such as `+02:00`, which decides the current
+/// date and time for `today` and `now`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UtcOffset(i64);
+
+impl UtcOffset {
+    pub fn seconds(&self) -> i64 {
+        self.0
+    }
+}
+
+impl FromStr for UtcOffset {
+    type Err = String;
+
+    /// Parses an offset in hours, such as `+2` or `-5`, or in hours and
+    /// minutes, such as `+05:30`. `UTC` and `Z` stand for an offset of zero.
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "unrecognized UTC offset {}; expected one like +02:00, -05:30, or UTC",
+                s
+            )
+        };
+        if s == "UTC" || s == "Z" {
+            return Ok(UtcOffset(0));
+        }
+        let (sign, rest) = match (s.strip_prefix('+'), s.strip_prefix('-')) {
+            (Some(rest), _) => (1, rest),
+            (_, Some(rest)) => (-1, rest),
+            _ => return Err(invalid()),
+        };
+        let (hours, minutes) = rest.split_once(':').unwrap_or((rest, "0"));
+        let hours: i64 = hours.parse().map_err(|_| invalid())?;
+        let minutes: i64 = minutes.parse().map_err(|_| invalid())?;
+        if !(0..=14).contains(&hours) || !(0..60).contains(&minutes) {
+            return Err(invalid());
+        }
+        Ok(UtcOffset(sign * (hours * 3600 + minutes * 60)))
+    }
+}
+
+impl Display for UtcOffset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sign = if self.0 < 0 { '-' } else { '+' };
+        let minutes = self.0.abs() / 60;
+        write!(f, "{}{:02}:{:02}", sign, minutes / 60, minutes % 60)
+    }
+}
+
+/// Determines whether a multiplication can be written without an operator,
+/// as in `2(3 + 4)`, `(1 + 2)(3 + 4)`, or `3pi`, and how tightly it binds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ImplicitMultiplication {
+    /// Every multiplication needs an operator, and adjacent operands are an error.
+    #[default]
+    Off,
+    /// Adjacent operands are multiplied with the same precedence as `*`, so
+    /// `1 / 2x` is `(1 / 2) * x`.
+    Standard,
+    /// Adjacent operands are multiplied before `*` and `/`, as is common in
+    /// textbooks, so `1 / 2x` is `1 / (2 * x)`. Exponents still bind more
+    /// tightly, so `2x^2` is `2 * (x^2)`.
+    Tight,
+}
+
+impl FromStr for ImplicitMultiplication {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "off" => Ok(ImplicitMultiplication::Off),
+            "standard" => Ok(ImplicitMultiplication::Standard),
+            "tight" => Ok(ImplicitMultiplication::Tight),
+            _ => Err(format!(
+                "unrecognized implicit multiplication mode {}; expected one of off, standard, tight",
+                s
+            )),
+        }
+    }
+}
+
+/// Settings that control how a single evaluation is carried out and how
+/// its result is displayed.
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub overflow: OverflowPolicy,
+    pub mode: NumericMode,
+    pub rational_format: RationalFormat,
+    pub complex_format: ComplexFormat,
+    /// The number of digits after the decimal point when displaying
+    /// rationals in decimal format, up to `MAX_DIGITS`.
+    pub digits: usize,
+    /// The radix, from 2 to 36, used when displaying integers.
+    pub radix: u32,
+    pub angle: AngleMode,
+    pub implicit_multiplication: ImplicitMultiplication,
+    /// The exchange rates that currency codes, such as `USD`, convert with.
+    /// Currencies are unknown without them.
+    pub rates: Option<Rc<Rates>>,
+    /// The offset from UTC of the local time, which `now` and `today` are in.
+    pub utc_offset: UtcOffset,
+    /// The modulus of modular arithmetic, in which integers are residues and
+    /// arithmetic wraps around, or `None` for ordinary arithmetic.
+    pub modulus: Option<BigInt>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            overflow: OverflowPolicy::default(),
+            mode: NumericMode::default(),
+            rational_format: RationalFormat::default(),
+            complex_format: ComplexFormat::default(),
+            digits: 10,
+            radix: 10,
+            angle: AngleMode::default(),
+            implicit_multiplication: ImplicitMultiplication::default(),
+            rates: None,
+            utc_offset: UtcOffset::default(),
+            modulus: None,
+        }
+    }
+}
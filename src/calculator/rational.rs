@@ -0,0 +1,319 @@
+use std::{
+    cmp::Ordering,
+    fmt::Display,
+    ops::{Add, Div, Mul, Neg, Sub},
+};
+
+use super::bigint::BigInt;
+
+/// An exact fraction of two arbitrary-precision integers. Rationals are
+/// always stored in lowest terms with a positive denominator, so the derived
+/// equality is structural.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rational {
+    numerator: BigInt,
+    denominator: BigInt,
+}
+
+impl Rational {
+    /// Creates a rational number in lowest terms. Panics if the denominator is zero.
+    pub fn new(numerator: BigInt, denominator: BigInt) -> Rational {
+        assert!(
+            !denominator.is_zero(),
+            "attempted to create a Rational with a zero denominator"
+        );
+        let divisor = numerator.gcd(&denominator);
+        let divisor = if denominator.is_negative() {
+            -divisor
+        } else {
+            divisor
+        };
+        Rational {
+            numerator: numerator.div_rem(&divisor).0,
+            denominator: denominator.div_rem(&divisor).0,
+        }
+    }
+
+    /// Parses a string of decimal digits with an optional decimal point, such
+    /// as `3.25`, `.5`, or `5.`, into the exact fraction that it represents.
+    pub fn from_decimal_str(s: &str) -> Option<Rational> {
+        let (whole, fraction) = s.split_once('.').unwrap_or((s, ""));
+        if whole.is_empty() && fraction.is_empty() {
+            return None;
+        }
+        let digits = format!("{}{}", whole, fraction);
+        let numerator: BigInt = digits.parse().ok()?;
+        let denominator = BigInt::from(10).pow(fraction.len() as u32);
+        Some(Rational::new(numerator, denominator))
+    }
+
+    pub fn numerator(&self) -> &BigInt {
+        &self.numerator
+    }
+
+    pub fn denominator(&self) -> &BigInt {
+        &self.denominator
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denominator == BigInt::from(1)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.numerator.is_zero()
+    }
+
+    /// Returns the largest integer that is less than or equal to this rational.
+    pub fn floor(&self) -> BigInt {
+        self.numerator.div_floor(&self.denominator)
+    }
+
+    /// Returns the smallest integer that is greater than or equal to this rational.
+    pub fn ceil(&self) -> BigInt {
+        -(-self.clone()).floor()
+    }
+
+    /// Returns the nearest integer to this rational, rounding half away from zero.
+    pub fn round(&self) -> BigInt {
+        let doubled = &(&self.numerator.abs() * &BigInt::from(2)) + &self.denominator;
+        let rounded = doubled.div_rem(&(&self.denominator * &BigInt::from(2))).0;
+        if self.numerator.is_negative() {
+            -rounded
+        } else {
+            rounded
+        }
+    }
+
+    /// Returns the nearest integer to this rational, rounding half to the
+    /// nearest even integer, as banks do, so that ties do not drift upward.
+    pub fn round_half_even(&self) -> BigInt {
+        let floor = self.floor();
+        let doubled_fraction =
+            &(&self.numerator - &(&floor * &self.denominator)) * &BigInt::from(2);
+        match doubled_fraction.cmp(&self.denominator) {
+            Ordering::Less => floor,
+            Ordering::Equal if !floor.is_odd() => floor,
+            _ => &floor + &BigInt::from(1),
+        }
+    }
+
+    /// Returns the integer part of this rational, rounding toward zero.
+    pub fn trunc(&self) -> BigInt {
+        self.numerator.div_rem(&self.denominator).0
+    }
+
+    /// Converts this rational to the nearest double-precision float. Terms
+    /// that fit in a float exactly are divided directly, which rounds
+    /// correctly, so `26.8224` stays `26.8224`. Otherwise the quotient is
+    /// computed to 55 significant bits, whatever the size of the terms, with
+    /// the lowest bit set when it is inexact so that it still rounds
+    /// correctly, and then scaled by the matching power of two.
+    pub fn to_f64(&self) -> f64 {
+        if self.numerator.bits() <= f64::MANTISSA_DIGITS as usize
+            && self.denominator.bits() <= f64::MANTISSA_DIGITS as usize
+        {
+            return self.numerator.to_f64() / self.denominator.to_f64();
+        }
+        let shift = 55 + self.denominator.bits() as i64 - self.numerator.bits() as i64;
+        let (numerator, denominator) = match shift >= 0 {
+            true => (
+                &self.numerator.abs() << shift as usize,
+                self.denominator.clone(),
+            ),
+            false => (self.numerator.abs(), &self.denominator << -shift as usize),
+        };
+        let (quotient, remainder) = numerator.div_rem(&denominator);
+        let quotient = match remainder.is_zero() {
+            true => quotient,
+            false => &quotient | &BigInt::from(1),
+        };
+        // Results that need a larger power of two are zero or infinite anyway
+        let mut exponent = (-shift).clamp(-2200, 2200) as i32;
+        let mut magnitude = quotient.to_f64();
+        while exponent != 0 {
+            let step = exponent.clamp(-1000, 1000);
+            magnitude *= 2f64.powi(step);
+            exponent -= step;
+        }
+        match self.numerator.is_negative() {
+            true => -magnitude,
+            false => magnitude,
+        }
+    }
+
+    /// Formats this rational as a whole number followed by a proper fraction,
+    /// such as `1 1/2`. Values with no whole part are formatted as plain fractions.
+    pub fn to_mixed_string(&self) -> String {
+        let (whole, remainder) = self.numerator.div_rem(&self.denominator);
+        if whole.is_zero() || remainder.is_zero() {
+            return self.to_string();
+        }
+        format!("{} {}/{}", whole, remainder.abs(), self.denominator)
+    }
+
+    /// Formats this rational in decimal notation with exactly the given number
+    /// of digits after the decimal point, rounding half away from zero.
+    pub fn to_decimal_string(&self, digits: usize) -> String {
+        let scale = BigInt::from(10).pow(digits as u32);
+        let scaled = &(&self.numerator.abs() * &scale) * &BigInt::from(2);
+        let doubled_denominator = &self.denominator * &BigInt::from(2);
+        let rounded = (&scaled + &self.denominator)
+            .div_rem(&doubled_denominator)
+            .0
+            .to_string();
+        let padded = format!("{:0>width$}", rounded, width = digits + 1);
+        let (whole, fraction) = padded.split_at(padded.len() - digits);
+        let sign = if self.numerator.is_negative() && padded.bytes().any(|b| b != b'0') {
+            "-"
+        } else {
+            ""
+        };
+        if digits == 0 {
+            format!("{}{}", sign, whole)
+        } else {
+            format!("{}{}.{}", sign, whole, fraction)
+        }
+    }
+}
+
+impl From<BigInt> for Rational {
+    fn from(value: BigInt) -> Self {
+        Rational {
+            numerator: value,
+            denominator: BigInt::from(1),
+        }
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.numerator)
+        } else {
+            write!(f, "{}/{}", self.numerator, self.denominator)
+        }
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Rational {
+    /// Compares two rationals by cross-multiplying, which is valid because
+    /// denominators are always positive.
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.numerator * &other.denominator).cmp(&(&other.numerator * &self.denominator))
+    }
+}
+
+impl Neg for Rational {
+    type Output = Rational;
+
+    fn neg(self) -> Rational {
+        Rational {
+            numerator: -self.numerator,
+            denominator: self.denominator,
+        }
+    }
+}
+
+impl Add for &Rational {
+    type Output = Rational;
+
+    fn add(self, other: &Rational) -> Rational {
+        Rational::new(
+            &(&self.numerator * &other.denominator) + &(&other.numerator * &self.denominator),
+            &self.denominator * &other.denominator,
+        )
+    }
+}
+
+impl Sub for &Rational {
+    type Output = Rational;
+
+    fn sub(self, other: &Rational) -> Rational {
+        Rational::new(
+            &(&self.numerator * &other.denominator) - &(&other.numerator * &self.denominator),
+            &self.denominator * &other.denominator,
+        )
+    }
+}
+
+impl Mul for &Rational {
+    type Output = Rational;
+
+    fn mul(self, other: &Rational) -> Rational {
+        Rational::new(
+            &self.numerator * &other.numerator,
+            &self.denominator * &other.denominator,
+        )
+    }
+}
+
+impl Div for &Rational {
+    type Output = Rational;
+
+    /// Divides two rationals. Panics if the divisor is zero.
+    fn div(self, other: &Rational) -> Rational {
+        Rational::new(
+            &self.numerator * &other.denominator,
+            &self.denominator * &other.numerator,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rational;
+
+    fn ratio(n: i32, d: i32) -> Rational {
+        Rational::new(n.into(), d.into())
+    }
+
+    #[test]
+    fn rationals_are_kept_in_lowest_terms() {
+        assert_eq!(ratio(2, 4), ratio(1, 2));
+        assert_eq!(ratio(3, -6).to_string(), "-1/2");
+        assert_eq!(&ratio(1, 3) + &ratio(1, 6), ratio(1, 2));
+        assert_eq!(Rational::from_decimal_str("0.10").unwrap(), ratio(1, 10));
+    }
+
+    #[test]
+    fn rationals_format_as_mixed_numbers_and_decimals() {
+        assert_eq!(ratio(3, 2).to_mixed_string(), "1 1/2");
+        assert_eq!(ratio(-7, 3).to_mixed_string(), "-2 1/3");
+        assert_eq!(ratio(1, 3).to_mixed_string(), "1/3");
+        assert_eq!(ratio(2, 3).to_decimal_string(4), "0.6667");
+        assert_eq!(ratio(-1, 8).to_decimal_string(2), "-0.13");
+        assert_eq!(ratio(-1, 1000).to_decimal_string(2), "0.00");
+        assert_eq!(ratio(5, 2).to_decimal_string(0), "3");
+    }
+
+    #[test]
+    fn rationals_round_and_compare_correctly() {
+        assert_eq!(ratio(-7, 2).floor().to_string(), "-4");
+        assert_eq!(ratio(-7, 2).ceil().to_string(), "-3");
+        assert_eq!(ratio(-7, 2).round().to_string(), "-4");
+        assert_eq!(ratio(7, 3).round().to_string(), "2");
+        assert!(ratio(1, 3) < ratio(1, 2));
+        assert!(ratio(-1, 2) < ratio(-1, 3));
+    }
+
+    #[test]
+    fn rationals_convert_to_the_nearest_float_at_any_scale() {
+        let decimal = |digits| Rational::from_decimal_str(digits).unwrap();
+        assert_eq!(decimal("26.8224").to_f64(), 26.8224);
+        assert_eq!(decimal("0.0000000000000000001").to_f64(), 1e-19);
+        assert_eq!(decimal("-0.00000000000000000001").to_f64(), -1e-20);
+        assert_eq!(
+            decimal("123456789012345678901.5").to_f64(),
+            123456789012345678901.5
+        );
+        let tiny = &ratio(1, 1) / &Rational::from(super::BigInt::from(10).pow(400));
+        assert_eq!(tiny.to_f64(), 0.0);
+        assert_eq!(ratio(1, 3).to_f64(), 1.0 / 3.0);
+    }
+}
@@ -1,19 +1,327 @@
 use std::fmt::Display;
 
-#[derive(Debug, PartialEq, Eq, Hash)]
+use super::{
+    bigint::BigInt, complex::Complex, context::MAX_CALL_DEPTH, date::DateTime, number::Number,
+    units::Quantity, value::Value,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Operator {
     Add,
     Subtract,
     Multiply,
+    /// A multiplication written without an operator, such as `2x`, when it
+    /// binds more tightly than `*`.
+    ImplicitMultiply,
     Divide,
+    FloorDivide,
+    Remainder,
+    Modulo,
+    Power,
+    ShiftLeft,
+    ShiftRight,
+    LogicalShiftRight,
+    BitwiseAnd,
+    BitwiseXor,
+    BitwiseOr,
+    Negate,
+    Identity,
+    BitwiseNot,
+    Factorial,
+    DoubleFactorial,
+    Percent,
+    Of,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    And,
+    Or,
+    Not,
+    /// The `?` that separates the condition of a conditional expression, such
+    /// as `x > 0 ? x : -x`, from the value it has when the condition is true.
+    Conditional,
+    /// The `:` that separates the two values of a conditional expression.
+    ConditionalElse,
+    /// The `∠` that creates a complex number from its distance from zero and
+    /// its angle, such as `2∠90`.
+    Polar,
+    /// The `to` or `in` that converts a quantity to another unit, such as
+    /// `60 mph to km/h`.
+    Convert,
+}
+
+/// Describes how a sequence of operators with the same precedence is grouped.
+/// Left-associative operators group from the left, so `8 - 4 - 2` is `(8 - 4) - 2`,
+/// and right-associative operators group from the right.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Associativity {
+    Left,
+    Right,
+}
+
+impl Operator {
+    /// Returns whether this is a prefix operator that takes a single operand.
+    pub fn is_unary(&self) -> bool {
+        matches!(
+            self,
+            Operator::Negate | Operator::Identity | Operator::BitwiseNot | Operator::Not
+        )
+    }
+
+    /// Returns whether this is a postfix operator that takes the single
+    /// operand before it, like the `!` in `5!`.
+    pub fn is_postfix(&self) -> bool {
+        matches!(
+            self,
+            Operator::Factorial | Operator::DoubleFactorial | Operator::Percent
+        )
+    }
+
+    /// Returns the operator spelled as a word, like `mod`, if there is one.
+    pub fn from_keyword(word: &str) -> Option<Operator> {
+        match word {
+            "mod" => Some(Operator::Modulo),
+            "xor" => Some(Operator::BitwiseXor),
+            "of" => Some(Operator::Of),
+            "and" => Some(Operator::And),
+            "or" => Some(Operator::Or),
+            "not" => Some(Operator::Not),
+            "to" | "in" => Some(Operator::Convert),
+            _ => None,
+        }
+    }
+
+    /// Returns the precedence and associativity of this operator. Operators
+    /// with higher precedence bind more tightly. Precedence levels are spaced
+    /// out so that new operators can be slotted in between existing ones.
+    pub fn properties(&self) -> (u8, Associativity) {
+        match self {
+            Operator::Conditional | Operator::ConditionalElse => (0, Associativity::Right),
+            Operator::Or | Operator::Convert => (1, Associativity::Left),
+            Operator::And => (2, Associativity::Left),
+            Operator::Not => (3, Associativity::Right),
+            Operator::BitwiseOr => (4, Associativity::Left),
+            Operator::BitwiseXor => (5, Associativity::Left),
+            Operator::BitwiseAnd => (6, Associativity::Left),
+            Operator::Equal | Operator::NotEqual => (7, Associativity::Left),
+            Operator::Less | Operator::LessEqual | Operator::Greater | Operator::GreaterEqual => {
+                (8, Associativity::Left)
+            }
+            Operator::ShiftLeft | Operator::ShiftRight | Operator::LogicalShiftRight => {
+                (9, Associativity::Left)
+            }
+            Operator::Add | Operator::Subtract => (10, Associativity::Left),
+            Operator::Multiply
+            | Operator::Divide
+            | Operator::FloorDivide
+            | Operator::Remainder
+            | Operator::Modulo
+            | Operator::Of => (20, Associativity::Left),
+            Operator::ImplicitMultiply => (25, Associativity::Left),
+            Operator::Polar => (27, Associativity::Left),
+            Operator::Negate | Operator::Identity | Operator::BitwiseNot => {
+                (30, Associativity::Right)
+            }
+            Operator::Power => (40, Associativity::Right),
+            Operator::Factorial | Operator::DoubleFactorial | Operator::Percent => {
+                (50, Associativity::Left)
+            }
+        }
+    }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+impl Display for Operator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let symbol = match self {
+            Operator::Add => "+",
+            Operator::Subtract => "-",
+            Operator::Multiply | Operator::ImplicitMultiply => "*",
+            Operator::Divide => "/",
+            Operator::FloorDivide => "//",
+            Operator::Remainder => "%",
+            Operator::Modulo => "mod",
+            Operator::Power => "^",
+            Operator::ShiftLeft => "<<",
+            Operator::ShiftRight => ">>",
+            Operator::LogicalShiftRight => ">>>",
+            Operator::BitwiseAnd => "&",
+            Operator::BitwiseXor => "xor",
+            Operator::BitwiseOr => "|",
+            Operator::Negate => "-",
+            Operator::Identity => "+",
+            Operator::BitwiseNot => "~",
+            Operator::Factorial => "!",
+            Operator::DoubleFactorial => "!!",
+            Operator::Percent => "%",
+            Operator::Of => "of",
+            Operator::Equal => "==",
+            Operator::NotEqual => "!=",
+            Operator::Less => "<",
+            Operator::LessEqual => "<=",
+            Operator::Greater => ">",
+            Operator::GreaterEqual => ">=",
+            Operator::And => "and",
+            Operator::Or => "or",
+            Operator::Not => "not",
+            Operator::Conditional => "?",
+            Operator::ConditionalElse => ":",
+            Operator::Polar => "∠",
+            Operator::Convert => "to",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+/// Describes how many arguments a function accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arity {
+    Exactly(usize),
+    Between(usize, usize),
+    AtLeast(usize),
+}
+
+impl Arity {
+    /// Returns whether a call with the given number of arguments is allowed.
+    pub fn accepts(&self, count: usize) -> bool {
+        match *self {
+            Arity::Exactly(n) => count == n,
+            Arity::Between(min, max) => (min..=max).contains(&count),
+            Arity::AtLeast(min) => count >= min,
+        }
+    }
+}
+
+impl Display for Arity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let plural = |n: usize| if n == 1 { "argument" } else { "arguments" };
+        match *self {
+            Arity::Exactly(n) => write!(f, "{} {}", n, plural(n)),
+            Arity::Between(min, max) => write!(f, "{} to {} {}", min, max, plural(max)),
+            Arity::AtLeast(min) => write!(f, "at least {} {}", min, plural(min)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Token {
-    Operand(isize),
+    Operand(Number),
+    /// The literal `true` or `false`.
+    Boolean(bool),
+    /// An imaginary literal, such as `2i`, holding the number before the `i`.
+    Imaginary(Number),
+    /// A date literal, such as `2026-10-18` or `2026-10-18T14:30`.
+    Date(DateTime),
+    /// A duration literal, such as `3h 20m`, or a time of day, such as
+    /// `14:30`, which is the time since midnight.
+    Duration(Quantity),
     Operator(Operator),
     LeftParen,
     RightParen,
+    /// The name of a function and its position in the input. It is always
+    /// followed by a parenthesized argument list.
+    Function(String, usize),
+    /// The name of a value, such as the constant `pi`, and its position in the input.
+    Identifier(String, usize),
+    Comma,
+    Assign,
+    Semicolon,
+    /// A function call in postfix notation, holding the name of the function,
+    /// the number of arguments it was given, and its position in the input.
+    Call(String, usize, usize),
+    /// A jump in postfix notation that skips the given number of tokens, which
+    /// passes over the value of a conditional expression that is not taken.
+    Jump(usize),
+    /// A jump in postfix notation that takes the condition of a conditional
+    /// expression and skips the given number of tokens when it is false.
+    JumpUnless(usize),
+}
+
+impl Token {
+    /// Returns whether this token stands for a value by itself, like a
+    /// number or the name of a constant.
+    pub fn is_value(&self) -> bool {
+        matches!(
+            self,
+            Token::Operand(_)
+                | Token::Boolean(_)
+                | Token::Imaginary(_)
+                | Token::Date(_)
+                | Token::Duration(_)
+                | Token::Identifier(_, _)
+        )
+    }
+
+    /// Returns whether this token can be the last token of an operand, like
+    /// a value, a closing parenthesis, or a postfix operator.
+    pub fn ends_operand(&self) -> bool {
+        match self {
+            Token::Operator(operator) => operator.is_postfix(),
+            token => token.is_value() || *token == Token::RightParen,
+        }
+    }
+}
+
+/// A value on the operand stack while an expression is evaluated.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    Number(Number),
+    Complex(Complex),
+    Boolean(bool),
+    Quantity(Quantity),
+    Date(DateTime),
+    /// A percentage, such as `15%`, stored as a fraction, such as 0.15. It is
+    /// kept apart from other numbers because adding it to or subtracting it
+    /// from a number is relative to that number, so `200 + 15%` is 230.
+    Percent(Number),
+}
+
+impl Operand {
+    /// Returns the value that this operand stands for on its own, which is
+    /// the fraction for a percentage.
+    pub fn into_value(self) -> Value {
+        match self {
+            Operand::Number(number) | Operand::Percent(number) => Value::Number(number),
+            Operand::Complex(complex) => Value::Complex(complex),
+            Operand::Boolean(boolean) => Value::Boolean(boolean),
+            Operand::Quantity(quantity) => Value::Quantity(quantity),
+            Operand::Date(date) => Value::Date(date),
+        }
+    }
+
+    /// Returns the number that this operand stands for, or fails if it is a
+    /// boolean or a quantity, which the operator cannot be applied to.
+    pub fn into_number(self, operator: Operator) -> Result<Number> {
+        match self.into_value() {
+            Value::Number(number) => Ok(number),
+            value => Err(Error::InvalidOperand(operator, Box::new(value))),
+        }
+    }
+
+    /// Returns the complex number that this operand stands for, treating a
+    /// real number as a complex number with an imaginary part of zero, or
+    /// fails if it is a boolean.
+    pub fn into_complex(self, operator: Operator) -> Result<Complex> {
+        match self.into_value() {
+            Value::Number(number) => Ok(Complex::from(number)),
+            Value::Complex(complex) => Ok(complex),
+            value => Err(Error::InvalidOperand(operator, Box::new(value))),
+        }
+    }
+}
+
+impl From<Value> for Operand {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Number(number) => Operand::Number(number),
+            Value::Complex(complex) => Operand::Complex(complex),
+            Value::Boolean(boolean) => Operand::Boolean(boolean),
+            Value::Quantity(quantity) => Operand::Quantity(quantity),
+            Value::Date(date) => Operand::Date(date),
+        }
+    }
 }
 
 /// Defines the standard result type used across the Calculator interface.
@@ -26,7 +334,19 @@ pub enum Error {
     ZeroLengthExpression,
     InvalidExpression(String),
     MismatchedParentheses,
-    DivideByZero(isize, isize),
+    DivideByZero(Operator, Box<Value>, Box<Value>),
+    Overflow(Operator, Vec<BigInt>, usize),
+    FloatOverflow(Operator, Box<Value>, Box<Value>),
+    InvalidOperand(Operator, Box<Value>),
+    UnknownFunction(String, usize),
+    UnknownVariable(String, usize),
+    RecursionLimit(String, usize),
+    ArityMismatch(String, Arity, usize, usize),
+    InvalidArgument(String, Box<Value>),
+    DimensionMismatch(Operator, Box<Value>, Box<Value>),
+    NoInverse(BigInt, BigInt),
+    NoResidue(Box<Value>, BigInt),
+    ConflictingCongruences(Vec<(BigInt, BigInt)>),
 }
 
 impl Display for Error {
@@ -47,7 +367,87 @@ impl Display for Error {
             Error::MismatchedParentheses => {
                 write!(f, "input expression contains mismatched parentheses")
             }
-            Error::DivideByZero(a, b) => write!(f, "attempted to divide by zero: {} / {}", a, b),
+            Error::DivideByZero(operator, a, b) => {
+                write!(f, "attempted to divide by zero: {} {} {}", a, operator, b)
+            }
+            Error::Overflow(operator, operands, bits) => {
+                let operation = match operands.as_slice() {
+                    // A result that no operation produced, such as a literal
+                    [operand] if *operator == Operator::Identity => operand.to_string(),
+                    [operand] if operator.is_postfix() => format!("{}{}", operand, operator),
+                    [operand] if operand.is_negative() => format!("{}({})", operator, operand),
+                    [operand] => format!("{}{}", operator, operand),
+                    _ => operands
+                        .iter()
+                        .map(|operand| operand.to_string())
+                        .collect::<Vec<String>>()
+                        .join(&format!(" {} ", operator)),
+                };
+                write!(
+                    f,
+                    "result does not fit in a {}-bit integer: {}",
+                    bits, operation
+                )
+            }
+            Error::FloatOverflow(operator, a, b) => {
+                write!(
+                    f,
+                    "result is too large for a float: {} {} {}",
+                    a, operator, b
+                )
+            }
+            Error::InvalidOperand(operator, operand) => {
+                write!(f, "operator {} cannot be applied to {}", operator, operand)
+            }
+            Error::UnknownFunction(name, position) => write!(
+                f,
+                "encountered unknown function {} in expression at position {}",
+                name, position
+            ),
+            Error::UnknownVariable(name, position) => write!(
+                f,
+                "encountered unknown variable {} in expression at position {}",
+                name, position
+            ),
+            Error::RecursionLimit(name, position) => write!(
+                f,
+                "function {} at position {} exceeded the maximum call depth of {}",
+                name, position, MAX_CALL_DEPTH
+            ),
+            Error::ArityMismatch(name, arity, count, position) => write!(
+                f,
+                "function {} at position {} expects {} but was given {}",
+                name, position, arity, count
+            ),
+            Error::InvalidArgument(name, argument) => {
+                write!(f, "function {} cannot be applied to {}", name, argument)
+            }
+            Error::DimensionMismatch(operator, a, b) => write!(
+                f,
+                "operator {} cannot be applied to quantities of different dimensions: {} and {}",
+                operator, a, b
+            ),
+            Error::NoInverse(a, modulus) => {
+                write!(f, "{} has no inverse modulo {}", a, modulus)
+            }
+            Error::NoResidue(value, modulus) => {
+                write!(
+                    f,
+                    "{} is not an integer and has no residue modulo {}",
+                    value, modulus
+                )
+            }
+            Error::ConflictingCongruences(congruences) => {
+                let congruences: Vec<String> = congruences
+                    .iter()
+                    .map(|(residue, modulus)| format!("{} modulo {}", residue, modulus))
+                    .collect();
+                write!(
+                    f,
+                    "no integer is {} at the same time",
+                    congruences.join(" and ")
+                )
+            }
         }
     }
 }
@@ -0,0 +1,779 @@
+use std::{cmp::Ordering, fmt::Display};
+
+use super::{
+    bigint::BigInt,
+    currency, date,
+    number::Number,
+    options::{NumericMode, Options},
+    rational::Rational,
+    types::{Error, Operator, Result},
+    value::Value,
+    Calculator,
+};
+
+/// The names of the SI base units, followed by the bit as the unit of
+/// information, in the order that their exponents are stored in a dimension.
+const BASE_UNITS: [&str; 8] = ["m", "kg", "s", "A", "K", "mol", "cd", "bit"];
+
+/// The exponents of the base dimensions that make up a physical quantity,
+/// in the order of `BASE_UNITS`, followed by the exponent of money, which
+/// has no SI unit. Velocity, for example, is length to the power of 1 and
+/// time to the power of -1.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Dimension([i32; 9]);
+
+const LENGTH: Dimension = Dimension([1, 0, 0, 0, 0, 0, 0, 0, 0]);
+const MASS: Dimension = Dimension([0, 1, 0, 0, 0, 0, 0, 0, 0]);
+const TIME: Dimension = Dimension([0, 0, 1, 0, 0, 0, 0, 0, 0]);
+const CURRENT: Dimension = Dimension([0, 0, 0, 1, 0, 0, 0, 0, 0]);
+const TEMPERATURE: Dimension = Dimension([0, 0, 0, 0, 1, 0, 0, 0, 0]);
+const AMOUNT: Dimension = Dimension([0, 0, 0, 0, 0, 1, 0, 0, 0]);
+const LUMINOSITY: Dimension = Dimension([0, 0, 0, 0, 0, 0, 1, 0, 0]);
+const AREA: Dimension = Dimension([2, 0, 0, 0, 0, 0, 0, 0, 0]);
+const VOLUME: Dimension = Dimension([3, 0, 0, 0, 0, 0, 0, 0, 0]);
+const SPEED: Dimension = Dimension([1, 0, -1, 0, 0, 0, 0, 0, 0]);
+const FREQUENCY: Dimension = Dimension([0, 0, -1, 0, 0, 0, 0, 0, 0]);
+const FORCE: Dimension = Dimension([1, 1, -2, 0, 0, 0, 0, 0, 0]);
+const ENERGY: Dimension = Dimension([2, 1, -2, 0, 0, 0, 0, 0, 0]);
+const POWER: Dimension = Dimension([2, 1, -3, 0, 0, 0, 0, 0, 0]);
+const PRESSURE: Dimension = Dimension([-1, 1, -2, 0, 0, 0, 0, 0, 0]);
+const CHARGE: Dimension = Dimension([0, 0, 1, 1, 0, 0, 0, 0, 0]);
+const VOLTAGE: Dimension = Dimension([2, 1, -3, -1, 0, 0, 0, 0, 0]);
+const RESISTANCE: Dimension = Dimension([2, 1, -3, -2, 0, 0, 0, 0, 0]);
+const INFORMATION: Dimension = Dimension([0, 0, 0, 0, 0, 0, 0, 1, 0]);
+const MONEY: Dimension = Dimension([0, 0, 0, 0, 0, 0, 0, 0, 1]);
+
+impl Dimension {
+    /// Returns whether this is the dimension of a pure number.
+    pub fn is_none(&self) -> bool {
+        self.0.iter().all(|exponent| *exponent == 0)
+    }
+
+    /// Returns whether this dimension involves money, such as the dimension
+    /// of a price per kilogram.
+    fn involves_money(&self) -> bool {
+        self.0[BASE_UNITS.len()] != 0
+    }
+
+    /// Returns whether this dimension involves information, such as the
+    /// dimension of a data rate.
+    fn involves_information(&self) -> bool {
+        self.0[BASE_UNITS.len() - 1] != 0
+    }
+
+    fn combine(&self, other: &Dimension, sign: i32) -> Dimension {
+        let mut exponents = self.0;
+        for (exponent, other) in exponents.iter_mut().zip(other.0) {
+            *exponent += sign * other;
+        }
+        Dimension(exponents)
+    }
+
+    fn power(&self, exponent: i32) -> Option<Dimension> {
+        let mut exponents = self.0;
+        for base in exponents.iter_mut() {
+            *base = base.checked_mul(exponent)?;
+        }
+        Some(Dimension(exponents))
+    }
+}
+
+/// The built-in units, each with its name, its size in SI base units, and its
+/// dimension. Sizes are decimals or fractions of two decimals, so that they are
+/// exact. The coherent SI units, whose size is 1, come first, since results
+/// are displayed in the first unit of size 1 that matches their dimension.
+const UNITS: &[(&str, &str, Dimension)] = &[
+    ("m", "1", LENGTH),
+    ("kg", "1", MASS),
+    ("s", "1", TIME),
+    ("A", "1", CURRENT),
+    ("K", "1", TEMPERATURE),
+    ("mol", "1", AMOUNT),
+    ("cd", "1", LUMINOSITY),
+    ("bit", "1", INFORMATION),
+    ("Hz", "1", FREQUENCY),
+    ("N", "1", FORCE),
+    ("J", "1", ENERGY),
+    ("W", "1", POWER),
+    ("Pa", "1", PRESSURE),
+    ("C", "1", CHARGE),
+    ("V", "1", VOLTAGE),
+    ("Ω", "1", RESISTANCE),
+    ("ohm", "1", RESISTANCE),
+    // Length
+    ("km", "1000", LENGTH),
+    ("cm", "0.01", LENGTH),
+    ("mm", "0.001", LENGTH),
+    ("um", "0.000001", LENGTH),
+    ("nm", "0.000000001", LENGTH),
+    ("inch", "0.0254", LENGTH),
+    ("inches", "0.0254", LENGTH),
+    ("ft", "0.3048", LENGTH),
+    ("yd", "0.9144", LENGTH),
+    ("mi", "1609.344", LENGTH),
+    ("mile", "1609.344", LENGTH),
+    ("miles", "1609.344", LENGTH),
+    ("nmi", "1852", LENGTH),
+    // Area and volume
+    ("ha", "10000", AREA),
+    ("acre", "4046.8564224", AREA),
+    ("L", "0.001", VOLUME),
+    ("mL", "0.000001", VOLUME),
+    ("gal", "0.003785411784", VOLUME),
+    // Mass
+    ("g", "0.001", MASS),
+    ("mg", "0.000001", MASS),
+    ("t", "1000", MASS),
+    ("lb", "0.45359237", MASS),
+    ("oz", "0.028349523125", MASS),
+    // Time
+    ("ms", "0.001", TIME),
+    ("us", "0.000001", TIME),
+    ("ns", "0.000000001", TIME),
+    ("min", "60", TIME),
+    ("h", "3600", TIME),
+    ("day", "86400", TIME),
+    ("week", "604800", TIME),
+    ("d", "86400", TIME),
+    ("second", "1", TIME),
+    ("minute", "60", TIME),
+    ("hour", "3600", TIME),
+    ("seconds", "1", TIME),
+    ("minutes", "60", TIME),
+    ("hours", "3600", TIME),
+    ("days", "86400", TIME),
+    ("weeks", "604800", TIME),
+    // Information, whose multiples with SI and IEC prefixes, such as `MB`
+    // and `MiB`, are not listed but recognized by `data_unit`
+    ("B", "8", INFORMATION),
+    // Speed
+    ("mph", "1609.344/3600", SPEED),
+    ("kn", "1852/3600", SPEED),
+    // Frequency
+    ("kHz", "1000", FREQUENCY),
+    ("MHz", "1000000", FREQUENCY),
+    ("GHz", "1000000000", FREQUENCY),
+    // Force, energy, power, and pressure
+    ("kN", "1000", FORCE),
+    ("lbf", "4.4482216152605", FORCE),
+    ("kJ", "1000", ENERGY),
+    ("MJ", "1000000", ENERGY),
+    ("cal", "4.184", ENERGY),
+    ("kcal", "4184", ENERGY),
+    ("Wh", "3600", ENERGY),
+    ("kWh", "3600000", ENERGY),
+    ("eV", "0.0000000000000000001602176634", ENERGY),
+    ("mW", "0.001", POWER),
+    ("kW", "1000", POWER),
+    ("MW", "1000000", POWER),
+    ("hp", "745.69987158227022", POWER),
+    ("kPa", "1000", PRESSURE),
+    ("bar", "100000", PRESSURE),
+    ("atm", "101325", PRESSURE),
+    ("psi", "4.4482216152605/0.00064516", PRESSURE),
+    // Electricity
+    ("mA", "0.001", CURRENT),
+    ("mV", "0.001", VOLTAGE),
+    ("kV", "1000", VOLTAGE),
+    ("kΩ", "1000", RESISTANCE),
+    ("mAh", "3.6", CHARGE),
+    // Temperature differences, since a change of 1 degC is a change of 1 K
+    ("degC", "1", TEMPERATURE),
+];
+
+/// A unit of measurement, such as `km` or `km/h`, which is a multiple of a
+/// combination of SI base units.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unit {
+    /// The named units that make up this unit, each with its exponent, such
+    /// as `km` to the power of 1 and `h` to the power of -1 for `km/h`.
+    terms: Vec<(String, i32)>,
+    /// The size of the unit in SI base units, such as 1000 for `km`.
+    pub factor: Rational,
+    pub dimension: Dimension,
+    /// The unit as it was written before `simplify` renamed it, such as
+    /// `kg*m/s^2` for `N`, which is what a conversion to this unit shows.
+    written: Option<Box<Unit>>,
+}
+
+impl Unit {
+    fn named(name: &str, factor: Rational, dimension: Dimension) -> Unit {
+        Unit {
+            terms: vec![(name.to_string(), 1)],
+            factor,
+            dimension,
+            written: None,
+        }
+    }
+
+    /// Returns the unit of pure numbers.
+    fn one() -> Unit {
+        Unit {
+            terms: vec![],
+            factor: Rational::from(BigInt::from(1)),
+            dimension: Dimension::default(),
+            written: None,
+        }
+    }
+
+    /// Returns the name of this unit, such as `km/h`. Units with nothing to
+    /// divide by are written with negative exponents, such as `m^-1`.
+    pub fn name(&self) -> String {
+        let part = |name: &str, exponent: i32| match exponent {
+            1 => name.to_string(),
+            _ => format!("{}^{}", name, exponent),
+        };
+        let numerator: Vec<String> = self
+            .terms
+            .iter()
+            .filter(|(_, exponent)| *exponent > 0)
+            .map(|(name, exponent)| part(name, *exponent))
+            .collect();
+        let denominator: Vec<String> = self
+            .terms
+            .iter()
+            .filter(|(_, exponent)| *exponent < 0)
+            .map(|(name, exponent)| part(name, -exponent))
+            .collect();
+        match (numerator.is_empty(), denominator.len()) {
+            (true, _) => self
+                .terms
+                .iter()
+                .map(|(name, exponent)| part(name, *exponent))
+                .collect::<Vec<String>>()
+                .join("*"),
+            (false, 0) => numerator.join("*"),
+            (false, 1) => format!("{}/{}", numerator.join("*"), denominator[0]),
+            (false, _) => format!("{}/({})", numerator.join("*"), denominator.join("*")),
+        }
+    }
+
+    /// Multiplies this unit by another unit raised to the power of 1 or -1,
+    /// cancelling out named units that appear in both, so `km/h` times `h`
+    /// is `km`.
+    fn combine(self, other: Unit, sign: i32) -> Unit {
+        let (this, other) = (self.into_written(), other.into_written());
+        let mut terms = this.terms;
+        for (name, exponent) in other.terms {
+            match terms.iter_mut().find(|(existing, _)| *existing == name) {
+                Some((_, existing)) => *existing += sign * exponent,
+                None => terms.push((name, sign * exponent)),
+            }
+        }
+        terms.retain(|(_, exponent)| *exponent != 0);
+        let factor = match sign {
+            1 => &this.factor * &other.factor,
+            _ => &this.factor / &other.factor,
+        };
+        Unit {
+            terms,
+            factor,
+            dimension: this.dimension.combine(&other.dimension, sign),
+            written: None,
+        }
+        .simplify()
+    }
+
+    fn multiply(self, other: Unit) -> Unit {
+        self.combine(other, 1)
+    }
+
+    fn divide(self, other: Unit) -> Unit {
+        self.combine(other, -1)
+    }
+
+    fn power(self, exponent: i32) -> Option<Unit> {
+        let this = self.into_written();
+        let factor = power_of(&this.factor, exponent);
+        let terms = this
+            .terms
+            .into_iter()
+            .map(|(name, base)| Some((name, base.checked_mul(exponent)?)))
+            .collect::<Option<Vec<(String, i32)>>>()?;
+        Some(
+            Unit {
+                terms,
+                factor,
+                dimension: this.dimension.power(exponent)?,
+                written: None,
+            }
+            .simplify(),
+        )
+    }
+
+    /// Returns this unit as it was written, before it was simplified.
+    fn into_written(self) -> Unit {
+        match self.written {
+            Some(written) => *written,
+            None => self,
+        }
+    }
+
+    /// Simplifies this unit with `reduce`, keeping the unit as it was
+    /// written when that changes its name.
+    fn simplify(self) -> Unit {
+        let reduced = self.clone().reduce();
+        if reduced.terms == self.terms {
+            reduced
+        } else {
+            Unit {
+                written: Some(Box::new(self)),
+                ..reduced
+            }
+        }
+    }
+
+    /// Replaces a unit that is made up of coherent SI units, such as
+    /// `m/s^2*kg`, with the named SI unit for its dimension, such as `N`, or
+    /// with its SI base units, such as `m^2`. Other units keep their names,
+    /// so `km/h` stays as it is, as do units that involve money. Units of
+    /// information that cancel out are dropped, so `TiB*s/MB` is `s`, and a
+    /// unit of information times any units that cancel out, such as
+    /// `Mbit*h/s`, is the unit of information alone.
+    fn reduce(self) -> Unit {
+        let is_data = |name: &String| data_unit(name).is_some();
+        let terms: Vec<(String, i32)> = if !self.dimension.involves_information() {
+            let other_terms = self.terms.iter().filter(|(name, _)| !is_data(name));
+            other_terms.cloned().collect()
+        } else if self.dimension == INFORMATION {
+            let data_terms = self
+                .terms
+                .iter()
+                .filter(|(name, exponent)| is_data(name) && *exponent == 1);
+            data_terms.take(1).cloned().collect()
+        } else {
+            self.terms.clone()
+        };
+        if !terms.is_empty() && terms != self.terms {
+            let factor = terms.iter().try_fold(
+                Rational::from(BigInt::from(1)),
+                |factor, (name, exponent)| {
+                    let (term, _) = table_entry(name)?;
+                    Some(&factor * &power_of(&term, *exponent))
+                },
+            );
+            if let Some(factor) = factor {
+                return Unit {
+                    terms,
+                    factor,
+                    dimension: self.dimension,
+                    written: None,
+                }
+                .reduce();
+            }
+        }
+        if self.factor != Rational::from(BigInt::from(1)) || self.dimension.involves_money() {
+            return self;
+        }
+        let terms = match UNITS
+            .iter()
+            .find(|(_, factor, dimension)| *factor == "1" && *dimension == self.dimension)
+        {
+            Some((name, _, _)) => vec![(name.to_string(), 1)],
+            None => BASE_UNITS
+                .iter()
+                .zip(self.dimension.0)
+                .filter(|(_, exponent)| *exponent != 0)
+                .map(|(name, exponent)| (name.to_string(), exponent))
+                .collect(),
+        };
+        Unit { terms, ..self }
+    }
+}
+
+/// A number with a unit, such as `5 km`. The value is kept in SI base
+/// units, so quantities with the same dimension can be combined directly,
+/// and the unit only determines how the quantity is displayed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quantity {
+    pub value: Number,
+    /// The unit, which is boxed to keep quantities as small as other values.
+    pub unit: Box<Unit>,
+}
+
+impl Quantity {
+    /// Returns the value of this quantity in its unit, such as 5 for `5 km`.
+    fn magnitude(&self, options: &Options) -> Number {
+        let factor = Number::from(self.unit.factor.clone());
+        let magnitude = Calculator::divide(self.value.clone(), factor.clone(), options)
+            .unwrap_or_else(|_| Number::Float(self.value.to_f64() / factor.to_f64()));
+        Calculator::apply_numeric_mode(magnitude, options)
+    }
+
+    /// Formats this quantity for display in its unit using the formatting
+    /// settings from the options. An amount of a currency is rounded to the
+    /// minor unit of the currency, such as `1.50 USD`, and any quantity that
+    /// involves money is followed by the date of the exchange rates. The
+    /// output radix only applies to plain integers, so the magnitude is
+    /// always decimal.
+    pub fn format(&self, options: &Options) -> String {
+        let options = &Options {
+            radix: 10,
+            ..options.clone()
+        };
+        let Some(rates) = options
+            .rates
+            .as_ref()
+            .filter(|_| self.unit.dimension.involves_money())
+        else {
+            return format!(
+                "{} {}",
+                self.magnitude(options).format(options),
+                self.unit.name()
+            );
+        };
+        let is_currency = self.unit.dimension == MONEY && rates.rate(&self.unit.name()).is_some();
+        let magnitude = match exact(self.value.clone()) {
+            value if is_currency && value.is_exact() => {
+                let value = value.to_rational();
+                let digits = currency::minor_digits(&self.unit.name());
+                currency::round(&(&value / &self.unit.factor), digits).to_decimal_string(digits)
+            }
+            _ => self.magnitude(options).format(options),
+        };
+        format!(
+            "{} {} (rates of {})",
+            magnitude,
+            self.unit.name(),
+            rates.date
+        )
+    }
+}
+
+impl From<Number> for Quantity {
+    fn from(value: Number) -> Self {
+        Quantity {
+            value,
+            unit: Box::new(Unit::one()),
+        }
+    }
+}
+
+impl Display for Quantity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let magnitude = self.magnitude(&Options::default());
+        write!(f, "{} {}", magnitude, self.unit.name())
+    }
+}
+
+/// Returns one of the named unit, such as `1 km` for `km`, or `None` if there
+/// is no unit with that name. Currencies are units too when the options hold
+/// exchange rates, with the base currency of the rates as their SI unit.
+pub fn lookup(name: &str, options: &Options) -> Option<Quantity> {
+    let (factor, dimension) = match table_entry(name) {
+        Some(entry) => entry,
+        None => {
+            let rate = options.rates.as_ref()?.rate(name)?;
+            (&Rational::from(BigInt::from(1)) / rate, MONEY)
+        }
+    };
+    Some(Quantity {
+        value: Number::from(factor.clone()),
+        unit: Box::new(Unit::named(name, factor, dimension)),
+    })
+}
+
+/// Returns an amount of a unit from the built-in table, such as `200 min`
+/// for a duration. Panics if the table has no unit with that name.
+pub fn quantity(amount: Rational, name: &str) -> Quantity {
+    let (factor, dimension) = table_entry(name).expect("unit is in the table");
+    Quantity {
+        value: Number::from(&amount * &factor),
+        unit: Box::new(Unit::named(name, factor, dimension)),
+    }
+}
+
+/// Returns a duration as the nearest whole number of seconds, or `None` if
+/// the quantity is not a time or is too long.
+pub fn seconds(quantity: &Quantity) -> Option<i64> {
+    if quantity.unit.dimension != TIME {
+        return None;
+    }
+    match exact(quantity.value.clone()) {
+        Number::Float(_) => None,
+        value => date::to_i64(&value.to_rational().round()),
+    }
+}
+
+/// The decimal SI prefixes of units of information, which step by 1000, and
+/// the binary IEC prefixes, which step by 1024, in increasing order of size.
+const DATA_PREFIXES: [([&str; 7], i64); 2] = [
+    (["", "k", "M", "G", "T", "P", "E"], 1000),
+    (["", "Ki", "Mi", "Gi", "Ti", "Pi", "Ei"], 1024),
+];
+
+/// Splits the name of a unit of information, such as `MiB` or `kbit`, into
+/// the prefixes that it belongs with, the position of its own prefix among
+/// them, and the name of the unit without the prefix, or returns `None` if
+/// it is not a unit of information.
+fn data_unit(name: &str) -> Option<(&'static [&'static str; 7], i64, usize, &'static str)> {
+    ["bit", "B"].into_iter().find_map(|base| {
+        let prefix = name.strip_suffix(base)?;
+        DATA_PREFIXES.iter().find_map(|(prefixes, step)| {
+            let index = prefixes.iter().position(|known| *known == prefix)?;
+            Some((prefixes, *step, index, base))
+        })
+    })
+}
+
+/// The units that times in seconds, minutes, or hours are rescaled between,
+/// in increasing order of size. Days are left out, since a number of hours is
+/// usually more readable than a fraction of a day.
+const TIME_UNITS: [&str; 3] = ["s", "min", "h"];
+
+/// Changes the prefix of a data size or data rate to the largest one that
+/// leaves at least 1 of the unit, keeping to decimal or binary prefixes, so
+/// `1500 MB` becomes `1.5 GB` and `2048 KiB/s` becomes `2 MiB/s`. Times are
+/// changed to the largest of `TIME_UNITS` in the same way, so the time taken
+/// to transfer a data size at a data rate is `4.58 h` rather than `16492 s`.
+fn rescale(quantity: Quantity) -> Quantity {
+    let (name, time) = match quantity.unit.terms.as_slice() {
+        [(name, 1)] if TIME_UNITS.contains(&name.as_str()) => return rescale_time(quantity),
+        [(name, 1)] => (name, None),
+        [(name, 1), (time, -1)] => (name, Some(time)),
+        _ => return quantity,
+    };
+    let Some((prefixes, step, _, base)) = data_unit(name) else {
+        return quantity;
+    };
+    let per = match time.map(|time| table_entry(time)) {
+        None => Rational::from(BigInt::from(1)),
+        Some(Some((factor, TIME))) => factor,
+        Some(_) => return quantity,
+    };
+    // Guaranteed to exist because the base is in the table
+    let (base_factor, _) = table_entry(base).unwrap();
+    let amount = quantity.value.to_f64().abs() * per.to_f64() / base_factor.to_f64();
+    if amount == 0.0 || !amount.is_finite() {
+        return quantity;
+    }
+    let index = (1..prefixes.len())
+        .rev()
+        .find(|index| (step as f64).powi(*index as i32) <= amount)
+        .unwrap_or(0);
+    let prefixed = format!("{}{}", prefixes[index], base);
+    let (factor, _) = table_entry(&prefixed).unwrap(); // Guaranteed to exist because of data_unit
+    let mut terms = vec![(prefixed, 1)];
+    terms.extend(time.map(|time| (time.clone(), -1)));
+    Quantity {
+        value: quantity.value,
+        unit: Box::new(Unit {
+            terms,
+            factor: &factor / &per,
+            dimension: quantity.unit.dimension,
+            written: None,
+        }),
+    }
+}
+
+/// Changes the unit of a time to the largest of `TIME_UNITS` that leaves at
+/// least 1 of the unit.
+fn rescale_time(quantity: Quantity) -> Quantity {
+    let seconds = quantity.value.to_f64().abs();
+    if seconds == 0.0 || !seconds.is_finite() {
+        return quantity;
+    }
+    // Guaranteed to exist because each of the units is in the table
+    let (name, factor) = TIME_UNITS
+        .iter()
+        .rev()
+        .map(|name| (*name, table_entry(name).unwrap().0))
+        .find(|(_, factor)| factor.to_f64() <= seconds)
+        .unwrap_or_else(|| ("s", Rational::from(BigInt::from(1))));
+    Quantity {
+        value: quantity.value,
+        unit: Box::new(Unit {
+            terms: vec![(name.to_string(), 1)],
+            factor,
+            dimension: quantity.unit.dimension,
+            written: None,
+        }),
+    }
+}
+
+/// Raises the factor of a unit to an integer power.
+fn power_of(factor: &Rational, exponent: i32) -> Rational {
+    let magnitude = exponent.unsigned_abs();
+    let power = Rational::new(
+        factor.numerator().pow(magnitude),
+        factor.denominator().pow(magnitude),
+    );
+    match exponent < 0 {
+        true => &Rational::from(BigInt::from(1)) / &power,
+        false => power,
+    }
+}
+
+/// Returns the factor and dimension of a unit from the built-in table, or of
+/// a unit of information with a prefix.
+fn table_entry(name: &str) -> Option<(Rational, Dimension)> {
+    if let Some((_, step, index, base)) = data_unit(name).filter(|unit| unit.2 > 0) {
+        let (factor, dimension) = table_entry(base)?;
+        let scale = Rational::from(BigInt::from(step).pow(index as u32));
+        return Some((&factor * &scale, dimension));
+    }
+    let (_, factor, dimension) = UNITS.iter().find(|unit| unit.0 == name)?;
+    // Guaranteed to succeed because the table only holds valid decimals
+    let decimal = |digits| Rational::from_decimal_str(digits).unwrap();
+    let factor = match factor.split_once('/') {
+        Some((numerator, denominator)) => &decimal(numerator) / &decimal(denominator),
+        None => decimal(factor),
+    };
+    Some((factor, *dimension))
+}
+
+/// Returns whether an operator can be applied to quantities.
+pub fn supports(operator: Operator) -> bool {
+    matches!(
+        operator,
+        Operator::Add
+            | Operator::Subtract
+            | Operator::Multiply
+            | Operator::ImplicitMultiply
+            | Operator::Of
+            | Operator::Divide
+            | Operator::Power
+            | Operator::Convert
+    )
+}
+
+/// Turns a float into the shortest decimal that it stands for, which is how
+/// it would be displayed, so that `0.1` becomes exactly 1/10. Other numbers
+/// are returned unchanged.
+fn exact(number: Number) -> Number {
+    match number {
+        Number::Float(float) if float.is_finite() => {
+            // Guaranteed to succeed because finite floats are displayed as decimals
+            Number::from(Rational::from_decimal_str(&float.to_string()).unwrap())
+        }
+        number => number,
+    }
+}
+
+/// Converts a value to a quantity, treating a real number as a quantity
+/// without a dimension, or fails if it is neither.
+fn quantity_operand(operator: Operator, value: Value) -> Result<Quantity> {
+    match value {
+        Value::Number(number) => Ok(Quantity::from(number)),
+        Value::Quantity(quantity) => Ok(quantity),
+        value => Err(Error::InvalidOperand(operator, Box::new(value))),
+    }
+}
+
+/// Fails unless two quantities have the same dimension, since adding metres
+/// to seconds, for example, has no meaning.
+fn check_dimensions(operator: Operator, a: &Quantity, b: &Quantity) -> Result<()> {
+    if a.unit.dimension == b.unit.dimension {
+        Ok(())
+    } else {
+        Err(Error::DimensionMismatch(
+            operator,
+            Box::new(Value::from(a.clone())),
+            Box::new(Value::from(b.clone())),
+        ))
+    }
+}
+
+/// Applies an operator to two values, at least one of which is a quantity.
+/// Sums and differences take the unit of the left operand, so `5 km + 300 m`
+/// is `5.3 km`, while `to` converts the left operand to the unit on the right.
+pub fn apply_binary_operator(
+    operator: Operator,
+    a: Value,
+    b: Value,
+    options: &Options,
+) -> Result<Value> {
+    let mut a = quantity_operand(operator, a)?;
+    let mut b = quantity_operand(operator, b)?;
+    // In float mode, decimals such as the 1.5 in `1.5 km` are floats by the
+    // time they get here, so they are turned back into exact decimals, which
+    // keeps quantities, and amounts of money in particular, exact.
+    // The arithmetic is done in rational mode for the same reason, and the
+    // numeric mode applies when the result is displayed.
+    if options.mode == NumericMode::Float {
+        a.value = exact(a.value);
+        b.value = exact(b.value);
+    }
+    let options = &Options {
+        mode: NumericMode::Rational,
+        ..options.clone()
+    };
+    let quantity = match operator {
+        Operator::Add | Operator::Subtract => {
+            check_dimensions(operator, &a, &b)?;
+            let value = match operator {
+                Operator::Add => Calculator::add(a.value, b.value, options)?,
+                _ => Calculator::subtract(a.value, b.value, options)?,
+            };
+            Quantity {
+                value,
+                unit: a.unit,
+            }
+        }
+        Operator::Multiply | Operator::ImplicitMultiply | Operator::Of => Quantity {
+            value: Calculator::multiply(a.value, b.value, options)?,
+            unit: Box::new(a.unit.multiply(*b.unit)),
+        },
+        Operator::Divide => Quantity {
+            value: Calculator::divide(a.value, b.value, options)?,
+            unit: Box::new(a.unit.divide(*b.unit)),
+        },
+        Operator::Power => {
+            let exponent = match &b.value {
+                Number::Integer(exponent) if b.unit.dimension.is_none() => exponent
+                    .abs()
+                    .to_u64()
+                    .and_then(|magnitude| i32::try_from(magnitude).ok())
+                    .map(|magnitude| match exponent.is_negative() {
+                        true => -magnitude,
+                        false => magnitude,
+                    }),
+                _ => None,
+            };
+            let Some(exponent) = exponent else {
+                return Err(Error::InvalidOperand(operator, Box::new(Value::from(b))));
+            };
+            // The value is computed first, so that an exponent too large for
+            // the value fails before the factor of the unit is raised to it
+            let value = Calculator::power(a.value, b.value.clone(), options)?;
+            match a.unit.power(exponent) {
+                Some(unit) => Quantity {
+                    value,
+                    unit: Box::new(unit),
+                },
+                None => return Err(Error::InvalidOperand(operator, Box::new(Value::from(b)))),
+            }
+        }
+        Operator::Convert => {
+            if b.unit.dimension.is_none() {
+                return Err(Error::InvalidOperand(operator, Box::new(Value::from(b))));
+            }
+            check_dimensions(operator, &a, &b)?;
+            Quantity {
+                value: a.value,
+                unit: Box::new(b.unit.into_written()),
+            }
+        }
+        _ => unreachable!(), // Only called for operators that support quantities
+    };
+    Ok(Value::from(match operator {
+        Operator::Convert => quantity,
+        _ => rescale(quantity),
+    }))
+}
+
+pub fn negate(a: Quantity, options: &Options) -> Result<Quantity> {
+    Ok(Quantity {
+        value: Calculator::negate(a.value, options)?,
+        unit: a.unit,
+    })
+}
+
+/// Orders two values, at least one of which is a quantity, failing unless
+/// they have the same dimension.
+pub fn compare(operator: Operator, a: Value, b: Value) -> Result<Option<Ordering>> {
+    let a = quantity_operand(operator, a)?;
+    let b = quantity_operand(operator, b)?;
+    check_dimensions(operator, &a, &b)?;
+    Ok(a.value.partial_cmp(&b.value))
+}
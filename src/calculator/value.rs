@@ -0,0 +1,93 @@
+use std::fmt::Display;
+
+use super::{complex::Complex, date::DateTime, number::Number, options::Options, units::Quantity};
+
+/// A value produced during evaluation, which is either a real number, a
+/// complex number, a quantity with a unit, a date, or the boolean result of a
+/// comparison or logical operator.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Number(Number),
+    /// A complex number with a nonzero imaginary part. Complex numbers whose
+    /// imaginary part is zero are always turned into real numbers.
+    Complex(Complex),
+    Boolean(bool),
+    /// A number with a unit, such as `5 km`. Quantities without a dimension,
+    /// such as `km/m`, are always turned into real numbers.
+    Quantity(Quantity),
+    Date(DateTime),
+}
+
+impl Value {
+    /// Formats this value for display using the formatting settings from the
+    /// options. Booleans are printed as `true` or `false`.
+    pub fn format(&self, options: &Options) -> String {
+        match self {
+            Value::Number(number) => number.format(options),
+            Value::Complex(complex) => complex.format(options),
+            Value::Boolean(boolean) => boolean.to_string(),
+            Value::Quantity(quantity) => quantity.format(options),
+            Value::Date(date) => date.to_string(),
+        }
+    }
+}
+
+impl From<Number> for Value {
+    fn from(value: Number) -> Self {
+        Value::Number(value)
+    }
+}
+
+impl From<Complex> for Value {
+    /// Converts a complex number to a value, collapsing it to a real number
+    /// when its imaginary part is zero.
+    fn from(value: Complex) -> Self {
+        if value.im.is_zero() {
+            Value::Number(value.re)
+        } else {
+            Value::Complex(value)
+        }
+    }
+}
+
+impl From<Quantity> for Value {
+    /// Converts a quantity to a value, collapsing it to a real number when it
+    /// has no dimension.
+    fn from(value: Quantity) -> Self {
+        if value.unit.dimension.is_none() {
+            Value::Number(value.value)
+        } else {
+            Value::Quantity(value)
+        }
+    }
+}
+
+impl From<i32> for Value {
+    fn from(value: i32) -> Self {
+        Value::Number(value.into())
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Boolean(value)
+    }
+}
+
+impl PartialEq<Number> for Value {
+    fn eq(&self, other: &Number) -> bool {
+        matches!(self, Value::Number(number) if number == other)
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Number(number) => write!(f, "{}", number),
+            Value::Complex(complex) => write!(f, "{}", complex),
+            Value::Boolean(boolean) => write!(f, "{}", boolean),
+            Value::Quantity(quantity) => write!(f, "{}", quantity),
+            Value::Date(date) => write!(f, "{}", date),
+        }
+    }
+}
@@ -2,33 +2,76 @@ use std::{
     env,
     error::Error,
     io::{stdin, stdout, Write},
+    rc::Rc,
 };
 
-use crate::calculator::Calculator;
+use crate::calculator::{BigInt, Calculator, Context, Number, Options, Rates, Value, MAX_DIGITS};
 
 mod calculator;
 
 fn main() -> Result<(), Box<dyn Error>> {
-    let args: Vec<String> = env::args().collect();
+    let mut options = Options::default();
+    let mut expression: Vec<String> = vec![];
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        // Everything after a bare `--` is part of the expression, which allows
+        // expressions such as `--5` that would otherwise look like flags.
+        if arg == "--" {
+            expression.extend(args.by_ref());
+            break;
+        }
+        let flag = match arg.strip_prefix("--") {
+            Some(flag) => flag,
+            None => {
+                expression.push(arg);
+                continue;
+            }
+        };
+        let (name, value) = match flag.split_once('=') {
+            Some((name, value)) => (name, value.to_string()),
+            None => match args.next() {
+                Some(value) => (flag, value),
+                None => return Err(format!("flag --{} requires a value", flag).into()),
+            },
+        };
+        apply_setting(&mut options, name, &value)?;
+    }
+    let mut context = Context::new(options);
 
     // Scripting mode, just return result or fail
-    if args.len() > 1 {
-        let expression = args[1..].join("");
-        match Calculator::evaluate(&expression) {
-            Ok(result) => println!("{}", result),
+    if !expression.is_empty() {
+        // The arguments are separated by spaces, since keywords such as
+        // `mod` must not run into the operands next to them
+        let expression = expression.join(" ");
+        let result = Calculator::evaluate(&expression, &mut context);
+        for warning in context.take_warnings() {
+            eprintln!("Warning: {}", warning);
+        }
+        match result {
+            Ok(Some(result)) => println!("{}", result.format(&context.options)),
+            Ok(None) => (),
             Err(err) => return Err(format!("{}", err).into()),
         }
         return Ok(());
     }
 
     // Interactive mode, consume input until exit
-    let mut history = vec![0];
+    let mut history = vec![Value::Number(Number::Integer(0.into()))];
     println!("Using simple-calc in interactive mode.");
     println!("Use '$?' to access the previous result.");
     println!("Use '${{N}}' to go further back in history.");
+    println!("Use 'name = value' to assign a variable.");
+    println!("Use 'f(x, y) = expression' to define a function.");
+    println!("Use ':vars' to list variables and functions and ':del name' to delete one.");
+    println!("Use ':setting value' to change a setting, like ':radix 16'.");
     println!("Enter 'exit' to quit.");
     loop {
-        let prompt = ">> ";
+        // The prompt shows the angle mode and the modulus, since they
+        // silently change the results of trigonometric functions and arithmetic.
+        let prompt = match &context.options.modulus {
+            Some(modulus) => format!("[{} mod {}] >> ", context.options.angle, modulus),
+            None => format!("[{}] >> ", context.options.angle),
+        };
         print!("{}", prompt);
         stdout().flush().unwrap();
 
@@ -36,11 +79,11 @@ fn main() -> Result<(), Box<dyn Error>> {
         stdin()
             .read_line(&mut input)
             .expect("Unexpected error while reading input");
-        input = input.replace(prompt, "");
+        input = input.replace(&prompt, "");
         input = input.trim().into();
-        input = input.replace("$?", &history.last().unwrap().to_string());
+        input = input.replace("$?", &format!("({})", history.last().unwrap()));
         for (i, result) in history.iter().rev().enumerate() {
-            input = input.replace(&format!("${}", i), &result.to_string());
+            input = input.replace(&format!("${}", i), &format!("({})", result));
         }
 
         if input == "exit" {
@@ -48,12 +91,96 @@ fn main() -> Result<(), Box<dyn Error>> {
             return Ok(());
         }
 
-        match Calculator::evaluate(&input) {
-            Ok(result) => {
-                println!("The result is {}.", result);
+        if let Some(command) = input.strip_prefix(':') {
+            match command.split_once(' ') {
+                None if command == "vars" => {
+                    for (name, value) in context.variables() {
+                        println!("{} = {}", name, value.format(&context.options));
+                    }
+                    for (name, parameters) in context.functions() {
+                        println!("{}({})", name, parameters.join(", "));
+                    }
+                }
+                Some(("del", name)) => {
+                    let name = name.trim();
+                    if context.remove_variable(name).is_some() {
+                        println!("Deleted variable {}.", name);
+                    } else if context.remove_function(name) {
+                        println!("Deleted function {}.", name);
+                    } else {
+                        println!(
+                            "Encountered the following error unknown variable or function {}.",
+                            name
+                        );
+                    }
+                }
+                Some((name, value)) => {
+                    match apply_setting(&mut context.options, name, value.trim()) {
+                        Ok(()) => println!("Updated setting {}.", command.trim()),
+                        Err(err) => println!("Encountered the following error {}.", err),
+                    }
+                }
+                None => println!(
+                    "Encountered the following error setting {} requires a value.",
+                    command
+                ),
+            }
+            continue;
+        }
+
+        let result = Calculator::evaluate(&input, &mut context);
+        for warning in context.take_warnings() {
+            println!("Warning: {}.", warning);
+        }
+        match result {
+            Ok(Some(result)) => {
+                println!("The result is {}.", result.format(&context.options));
                 history.push(result);
             }
+            Ok(None) => println!("Defined the function."),
             Err(err) => println!("Encountered the following error {}.", err),
         }
     }
 }
+
+/// Applies a setting by name. Settings can be passed as flags on the command
+/// line, such as `--radix 16`, or changed in interactive mode, such as `:radix 16`.
+fn apply_setting(options: &mut Options, name: &str, value: &str) -> Result<(), Box<dyn Error>> {
+    match name {
+        "overflow" => options.overflow = value.parse()?,
+        "mode" => options.mode = value.parse()?,
+        "format" => options.rational_format = value.parse()?,
+        "complex" => options.complex_format = value.parse()?,
+        "digits" => match value.parse() {
+            Ok(digits) if digits <= MAX_DIGITS => options.digits = digits,
+            _ => {
+                let message = format!(
+                    "digits must be between 0 and {}, found {}",
+                    MAX_DIGITS, value
+                );
+                return Err(message.into());
+            }
+        },
+        "angle" => options.angle = value.parse()?,
+        "implicit" => options.implicit_multiplication = value.parse()?,
+        "rates" => options.rates = Some(Rc::new(Rates::load(value)?)),
+        "offset" => options.utc_offset = value.parse()?,
+        "mod" | "modulus" => {
+            options.modulus = match value.parse::<BigInt>() {
+                _ if value == "off" => None,
+                Ok(modulus) if modulus > BigInt::from(1) => Some(modulus),
+                _ => {
+                    let message =
+                        format!("modulus must be an integer above 1 or off, found {}", value);
+                    return Err(message.into());
+                }
+            }
+        }
+        "radix" => match value.parse() {
+            Ok(radix) if (2..=36).contains(&radix) => options.radix = radix,
+            _ => return Err(format!("radix must be between 2 and 36, found {}", value).into()),
+        },
+        _ => return Err(format!("unrecognized setting {}", name).into()),
+    }
+    Ok(())
+}